@@ -0,0 +1,65 @@
+use futures::{future, Stream};
+use serenity::gateway::Shard;
+use tokio::runtime::Runtime;
+use {spawn, Error, SharderOptions};
+
+/// Boots all shards on a newly created runtime, blocking the current thread
+/// until booting has completed, and returns the booted shards.
+///
+/// This is a convenience for scripts and small tools with a synchronous
+/// `main`, and does not require the caller to manage a `tokio` runtime.
+///
+/// The function creates and owns its own runtime, which is shut down before
+/// returning. Because the shards' connections are driven by that runtime, the
+/// returned shards are only suitable for inspection, such as of their shard
+/// info. This is unsuitable for long-running event loops: use [`spawn`] on a
+/// runtime you keep running instead.
+///
+/// # Examples
+///
+/// Boot the shards of a bot and print their shard info:
+///
+/// ```rust,no_run
+/// # extern crate serenity_sharder;
+/// #
+/// # use std::error::Error;
+/// #
+/// # fn main() -> Result<(), Box<Error>> {
+/// #
+/// use serenity_sharder::{self, SharderOptions};
+/// use std::env;
+///
+/// let token = env::var("DISCORD_TOKEN")?;
+/// let shards = serenity_sharder::boot_blocking(SharderOptions::new(token))?;
+///
+/// for shard in shards {
+///     println!("Booted shard {:?}", shard.shard_info());
+/// }
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Returns [`Error::Io`] if the runtime could not be created.
+///
+/// Returns any error that [`spawn`] returns, as well as the error of the boot
+/// loop if it failed, such as when the strategy couldn't be resolved.
+///
+/// # Panics
+///
+/// Panics if called from within a running `tokio` runtime.
+///
+/// [`Error::Io`]: enum.Error.html#variant.Io
+/// [`spawn`]: fn.spawn.html
+pub fn boot_blocking(options: SharderOptions) -> Result<Vec<Shard>, Error> {
+    let mut runtime = Runtime::new()?;
+
+    let spawner = runtime.block_on(future::lazy(move || spawn(options)))?;
+    let shards = runtime.block_on(spawner.collect())?;
+
+    debug!("Booted {} shards, shutting down runtime", shards.len());
+
+    Ok(shards)
+}
@@ -0,0 +1,112 @@
+use futures::Future;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tokio::timer::Delay;
+use Error;
+
+/// The minimum duration between identifies in the same bucket.
+const IDENTIFY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A bucket coordinating identifies between multiple sharders in the same
+/// process, so that they don't get rate limited.
+///
+/// Each sharder's boot loop only spaces out its own identifies. When multiple
+/// sharders are spawned in one process, such as when splitting a strategy,
+/// they can identify within the same 5 second window. Sharing one bucket via
+/// [`SharderOptions::identify_bucket`] makes every boot wait for a permit
+/// before identifying, allowing at most one identify per 5 seconds in each
+/// [`max_concurrency`] bucket across all of the sharders.
+///
+/// # Examples
+///
+/// Share a bucket between two sharders booting halves of 20 shards:
+///
+/// ```rust,no_run
+/// # extern crate serenity_sharder;
+/// #
+/// # use std::error::Error;
+/// #
+/// # fn main() -> Result<(), Box<Error>> {
+/// #
+/// use serenity_sharder::{IdentifyBucket, SharderOptions, ShardingStrategy};
+/// use std::{env, sync::Arc};
+///
+/// let token = env::var("DISCORD_TOKEN")?;
+/// let bucket = Arc::new(IdentifyBucket::new());
+///
+/// let first = SharderOptions::new(&token)
+///     .with_identify_bucket(Arc::clone(&bucket))
+///     .with_strategy(ShardingStrategy::Range(0, 9, 20));
+/// let second = SharderOptions::new(&token)
+///     .with_identify_bucket(bucket)
+///     .with_strategy(ShardingStrategy::Range(10, 19, 20));
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`SharderOptions::identify_bucket`]:
+/// struct.SharderOptions.html#structfield.identify_bucket
+/// [`max_concurrency`]:
+/// struct.SharderOptions.html#structfield.max_concurrency
+#[derive(Debug, Default)]
+pub struct IdentifyBucket {
+    next: Mutex<HashMap<u64, Instant>>,
+}
+
+impl IdentifyBucket {
+    /// Creates a new bucket which no identifies have been made through.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a future which resolves once an identify may be made in the
+    /// given concurrency bucket.
+    ///
+    /// The permit is reserved immediately, so callers are served in the order
+    /// that they acquire.
+    pub(crate) fn acquire(
+        &self,
+        key: u64,
+    ) -> impl Future<Item = (), Error = Error> + Send {
+        Delay::new(self.reserve(key, Instant::now())).from_err()
+    }
+
+    /// Reserves the next permit in the given concurrency bucket, returning
+    /// when it may be used.
+    fn reserve(&self, key: u64, now: Instant) -> Instant {
+        let mut next = self.next.lock().expect("Identify bucket poisoned");
+        let entry = next.entry(key).or_insert(now);
+        let at = (*entry).max(now);
+        *entry = at + IDENTIFY_INTERVAL;
+
+        at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use super::IdentifyBucket;
+
+    #[test]
+    fn test_reserve() {
+        let bucket = IdentifyBucket::new();
+        let now = Instant::now();
+        let interval = Duration::from_secs(5);
+
+        assert_eq!(bucket.reserve(0, now), now);
+        assert_eq!(bucket.reserve(0, now), now + interval);
+        assert_eq!(bucket.reserve(0, now), now + interval * 2);
+
+        // Other concurrency buckets are independent.
+        assert_eq!(bucket.reserve(1, now), now);
+
+        // Permits aren't reserved in the past.
+        let later = now + interval * 10;
+        assert_eq!(bucket.reserve(0, later), later);
+    }
+}
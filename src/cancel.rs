@@ -0,0 +1,197 @@
+use futures::{
+    task::{self, Task},
+    Async,
+    Future,
+    Poll,
+};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+    Mutex,
+};
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    tasks: Mutex<Vec<Task>>,
+}
+
+/// A token which, once cancelled, stops the boot loop of every sharder it was
+/// given to.
+///
+/// A token is created for every [`SharderOptions`], and can be replaced with
+/// one of your own via [`SharderOptions::cancellation_token`], for example to
+/// share a single token between a shutdown handler and multiple sharders. It
+/// can also be retrieved from a [`ShardSpawner`].
+///
+/// Cancellation is observed promptly, even while the boot loop is waiting
+/// between shard boots. Once cancelled, no further shards are booted and the
+/// [`ShardSpawner`] stream ends.
+///
+/// Shards that have already been yielded are owned by you, and so are not
+/// closed by the sharder. Select the processing of each shard against
+/// [`cancelled`] and drop the shard once it resolves to close its connection.
+///
+/// Tokens are cheap to clone, and all clones refer to the same token.
+///
+/// # Examples
+///
+/// Stop booting shards and close every booted shard once a token is
+/// cancelled:
+///
+/// ```rust,no_run
+/// # extern crate futures;
+/// # extern crate serenity_sharder;
+/// # extern crate tokio;
+/// #
+/// # use std::error::Error;
+/// #
+/// # fn main() -> Result<(), Box<Error>> {
+/// #
+/// use futures::{Future, Stream};
+/// use serenity_sharder::{self, CancellationToken, SharderOptions};
+/// use std::env;
+///
+/// let token = CancellationToken::new();
+///
+/// let mut options = SharderOptions::new(env::var("DISCORD_TOKEN")?);
+/// options.cancellation_token(token.clone());
+///
+/// let future = serenity_sharder::spawn(options)?.for_each(move |shard| {
+///     // Process the shard's messages here.
+///
+///     // Drop the shard to close its connection once the token is cancelled.
+///     tokio::spawn(token.cancelled().map(move |_| drop(shard)));
+///
+///     Ok(())
+/// }).map_err(|why| eprintln!("Error booting shards: {}", why));
+///
+/// // In a shutdown handler:
+/// // token.cancel();
+///
+/// tokio::run(future);
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`SharderOptions`]: struct.SharderOptions.html
+/// [`SharderOptions::cancellation_token`]:
+/// struct.SharderOptions.html#method.cancellation_token
+/// [`ShardSpawner`]: struct.ShardSpawner.html
+/// [`cancelled`]: #method.cancelled
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+impl CancellationToken {
+    /// Creates a new token which has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the token, waking everything waiting on it.
+    ///
+    /// Cancelling a token which has already been cancelled does nothing.
+    ///
+    /// # Logs
+    ///
+    /// [INFO] When the token is cancelled for the first time.
+    pub fn cancel(&self) {
+        if self.inner.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        info!("Cancellation token cancelled");
+
+        let mut tasks = self.inner.tasks.lock().expect("Tasks poisoned");
+
+        for task in tasks.drain(..) {
+            task.notify();
+        }
+    }
+
+    /// Returns a future which resolves once the token has been cancelled.
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled {
+            token: self.clone(),
+        }
+    }
+
+    /// Returns whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// A future which resolves once a [`CancellationToken`] has been cancelled.
+///
+/// This is returned by [`CancellationToken::cancelled`].
+///
+/// [`CancellationToken`]: struct.CancellationToken.html
+/// [`CancellationToken::cancelled`]:
+/// struct.CancellationToken.html#method.cancelled
+#[derive(Clone, Debug)]
+pub struct Cancelled {
+    token: CancellationToken,
+}
+
+impl Future for Cancelled {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if self.token.is_cancelled() {
+            return Ok(Async::Ready(()));
+        }
+
+        {
+            let mut tasks = self.token.inner.tasks
+                .lock()
+                .expect("Tasks poisoned");
+
+            if !tasks.iter().any(|task| task.will_notify_current()) {
+                tasks.push(task::current());
+            }
+        }
+
+        // The token may have been cancelled before the task was registered.
+        if self.token.is_cancelled() {
+            Ok(Async::Ready(()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::Future;
+    use std::{thread, time::Duration};
+    use super::CancellationToken;
+
+    #[test]
+    fn test_cancel() {
+        let token = CancellationToken::new();
+        assert!(!token.is_cancelled());
+
+        token.clone().cancel();
+        assert!(token.is_cancelled());
+        assert!(token.cancelled().wait().is_ok());
+    }
+
+    #[test]
+    fn test_cancel_wakes() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+
+            clone.cancel();
+        });
+
+        assert!(token.cancelled().wait().is_ok());
+    }
+}
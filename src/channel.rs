@@ -0,0 +1,213 @@
+use futures::{
+    sync::mpsc::{
+        self,
+        Receiver,
+        SendError,
+        Sender,
+        UnboundedReceiver,
+        UnboundedSender,
+    },
+    Poll,
+    Sink,
+    StartSend,
+    Stream,
+};
+use serenity::gateway::Shard;
+use Error;
+
+/// A shard booted by a boot loop, or the error which stopped it.
+pub(crate) type Boot = Result<Shard, Error>;
+
+/// Creates a channel for a boot loop to send shards to the spawner over.
+///
+/// The channel is bounded to the given capacity if any, in which case sending
+/// a shard waits until the spawner has received enough of the shards sent
+/// before it. Otherwise the channel is unbounded.
+pub(crate) fn channel(capacity: Option<u64>) -> (ShardSender, ShardReceiver) {
+    match capacity {
+        Some(capacity) => {
+            // Each sender is guaranteed a slot on top of the buffer, and the
+            // boot loop sends through a single sender.
+            let buffer = capacity.max(1) - 1;
+            let (tx, rx) = mpsc::channel(buffer as usize);
+
+            (ShardSender::Bounded(tx), ShardReceiver::Bounded(rx))
+        },
+        None => {
+            let (tx, rx) = mpsc::unbounded();
+
+            (ShardSender::Unbounded(tx), ShardReceiver::Unbounded(rx))
+        },
+    }
+}
+
+/// The sending half of a [`channel`].
+///
+/// [`channel`]: fn.channel.html
+#[derive(Clone)]
+pub(crate) enum ShardSender {
+    Bounded(Sender<Boot>),
+    Unbounded(UnboundedSender<Boot>),
+}
+
+impl ShardSender {
+    /// Returns whether the receiver has been closed or dropped.
+    pub fn is_closed(&self) -> bool {
+        match *self {
+            ShardSender::Bounded(ref tx) => tx.is_closed(),
+            ShardSender::Unbounded(ref tx) => tx.is_closed(),
+        }
+    }
+}
+
+impl Sink for ShardSender {
+    type SinkItem = Boot;
+    type SinkError = SendError<Boot>;
+
+    fn start_send(&mut self, item: Boot) -> StartSend<Boot, SendError<Boot>> {
+        match *self {
+            ShardSender::Bounded(ref mut tx) => tx.start_send(item),
+            ShardSender::Unbounded(ref mut tx) => tx.start_send(item),
+        }
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), SendError<Boot>> {
+        match *self {
+            ShardSender::Bounded(ref mut tx) => tx.poll_complete(),
+            ShardSender::Unbounded(ref mut tx) => tx.poll_complete(),
+        }
+    }
+}
+
+/// The receiving half of a [`channel`].
+///
+/// [`channel`]: fn.channel.html
+pub(crate) enum ShardReceiver {
+    Bounded(Receiver<Boot>),
+    Unbounded(UnboundedReceiver<Boot>),
+}
+
+impl ShardReceiver {
+    /// Closes the receiver, so that no further shards can be sent while those
+    /// already sent can still be received.
+    pub fn close(&mut self) {
+        match *self {
+            ShardReceiver::Bounded(ref mut rx) => rx.close(),
+            ShardReceiver::Unbounded(ref mut rx) => rx.close(),
+        }
+    }
+}
+
+impl From<UnboundedReceiver<Boot>> for ShardReceiver {
+    fn from(rx: UnboundedReceiver<Boot>) -> Self {
+        ShardReceiver::Unbounded(rx)
+    }
+}
+
+impl Stream for ShardReceiver {
+    type Item = Boot;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Boot>, ()> {
+        match *self {
+            ShardReceiver::Bounded(ref mut rx) => rx.poll(),
+            ShardReceiver::Unbounded(ref mut rx) => rx.poll(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{
+        executor::{self, Notify, NotifyHandle},
+        Async,
+        Future,
+        Sink,
+        Stream,
+    };
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+    use super::{channel, Boot, ShardSender};
+    use Error;
+
+    struct Flag(AtomicBool);
+
+    impl Notify for Flag {
+        fn notify(&self, _: usize) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn boot(shard_id: u64) -> Boot {
+        Err(Error::ShardPanicked {
+            shard_id,
+        })
+    }
+
+    fn shard_id(boot: Boot) -> Option<u64> {
+        match boot {
+            Err(Error::ShardPanicked { shard_id }) => Some(shard_id),
+            _ => None,
+        }
+    }
+
+    fn send(tx: ShardSender, shard_id: u64) -> ShardSender {
+        tx.send(boot(shard_id)).wait().ok().expect("Receiver dropped")
+    }
+
+    #[test]
+    fn test_bounded() {
+        let flag = Arc::new(Flag(AtomicBool::new(false)));
+        let notify = NotifyHandle::from(Arc::clone(&flag));
+        let (tx, mut rx) = channel(Some(2));
+        let tx = send(send(tx, 0), 1);
+
+        // The channel is full, so the third send waits for a receive.
+        let mut sending = executor::spawn(tx.send(boot(2)));
+        let poll = sending.poll_future_notify(&notify, 0);
+        assert!(poll.ok().map_or(false, |poll| poll.is_not_ready()));
+
+        let received = match rx.poll() {
+            Ok(Async::Ready(Some(boot))) => shard_id(boot),
+            _ => None,
+        };
+        assert_eq!(received, Some(0));
+        assert!(flag.0.swap(false, Ordering::SeqCst));
+        let tx = match sending.poll_future_notify(&notify, 0) {
+            Ok(Async::Ready(tx)) => tx,
+            _ => panic!("Send didn't complete"),
+        };
+
+        // Dropping the receiver fails the send instead of hanging.
+        let mut sending = executor::spawn(tx.send(boot(3)));
+        let poll = sending.poll_future_notify(&notify, 0);
+        assert!(poll.ok().map_or(false, |poll| poll.is_not_ready()));
+
+        drop(rx);
+        assert!(flag.0.load(Ordering::SeqCst));
+        let unsent = match sending.poll_future_notify(&notify, 0) {
+            Err(why) => shard_id(why.into_inner()),
+            Ok(_) => None,
+        };
+        assert_eq!(unsent, Some(3));
+    }
+
+    #[test]
+    fn test_unbounded() {
+        let (tx, rx) = channel(None);
+        let tx = (0..5).fold(tx, send);
+        assert!(!tx.is_closed());
+        drop(tx);
+
+        let received = rx.wait().filter_map(|boot| boot.ok()).map(shard_id);
+        assert_eq!(received.collect::<Vec<_>>(), vec![
+            Some(0),
+            Some(1),
+            Some(2),
+            Some(3),
+            Some(4),
+        ]);
+    }
+}
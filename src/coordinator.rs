@@ -0,0 +1,66 @@
+use futures::Future;
+use Error;
+
+/// A hook coordinating identifies between sharders in multiple processes,
+/// such as when the shards of a bot are split across machines.
+///
+/// An [`IdentifyBucket`] only spaces out identifies within one process.
+/// Install an implementation via [`SharderOptions::coordinator`] to back the
+/// identify rate limit with a shared service instead, such as a distributed
+/// lock in Redis. The boot loop waits for [`acquire`] to resolve before each
+/// identify, including retries.
+///
+/// Without a coordinator, identifies are only coordinated within the process.
+///
+/// # Examples
+///
+/// A coordinator which allows every identify immediately:
+///
+/// ```rust,no_run
+/// # extern crate futures;
+/// # extern crate serenity_sharder;
+/// #
+/// # use std::error::Error as StdError;
+/// #
+/// # fn main() -> Result<(), Box<StdError>> {
+/// #
+/// use futures::{future, Future};
+/// use serenity_sharder::{Coordinator, Error, SharderOptions};
+/// use std::{env, sync::Arc};
+///
+/// struct AllowAll;
+///
+/// impl Coordinator for AllowAll {
+///     fn acquire(
+///         &self,
+///         _shard_id: u64,
+///     ) -> Box<Future<Item = (), Error = Error> + Send> {
+///         // Wait for a distributed lock here instead.
+///         Box::new(future::ok(()))
+///     }
+/// }
+///
+/// let mut options = SharderOptions::new(env::var("DISCORD_TOKEN")?);
+/// options.coordinator(Arc::new(AllowAll));
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`IdentifyBucket`]: struct.IdentifyBucket.html
+/// [`SharderOptions::coordinator`]:
+/// struct.SharderOptions.html#structfield.coordinator
+/// [`acquire`]: #tymethod.acquire
+pub trait Coordinator: Send + Sync {
+    /// Returns a future which resolves once the shard with the given ID may
+    /// identify.
+    ///
+    /// Resolving to an error fails the attempt to boot the shard, which is
+    /// retried according to [`SharderOptions::boot_retries`] if the error is
+    /// recoverable.
+    ///
+    /// [`SharderOptions::boot_retries`]:
+    /// struct.SharderOptions.html#structfield.boot_retries
+    fn acquire(&self, shard_id: u64)
+        -> Box<Future<Item = (), Error = Error> + Send>;
+}
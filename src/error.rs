@@ -1,30 +1,483 @@
+use futures::sync::oneshot::Canceled;
 use serenity::Error as SerenityError;
 use std::{
     error::Error as StdError,
-    fmt::{Display, Formatter, Result as FmtResult},
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    io::Error as IoError,
+    time::Duration,
 };
 use tokio::{
     executor::SpawnError as ExecutorSpawnError,
     timer::Error as TimerError,
 };
 use tungstenite::Error as TungsteniteError;
+use BootFuture;
 
 /// Enum wrapping all of the library's potential errors.
-#[derive(Debug)]
+///
+/// Errors can be compared for equality, which is useful in tests. Errors from
+/// other crates can't be compared, so variants wrapping them are never equal,
+/// not even to themselves. Predicates such as [`is_serenity`] check the
+/// variant regardless of the wrapped error instead.
+///
+/// [`is_serenity`]: #method.is_serenity
 pub enum Error {
-    /// An error from the `serenity` crate, likely when booting a shard.
+    /// Booting a shard did not complete within the boot timeout.
+    ///
+    /// Refer to [`SharderOptions::boot_timeout`] for more information.
+    ///
+    /// [`SharderOptions::boot_timeout`]:
+    /// struct.SharderOptions.html#structfield.boot_timeout
+    BootTimeout {
+        /// The ID of the shard being booted.
+        shard_id: u64,
+    },
+    /// The sending half of a channel used internally was dropped before
+    /// sending, likely because the sharder is being torn down.
+    Canceled(Canceled),
+    /// The gateway closed the connection of a shard while booting it.
+    ///
+    /// Some close codes can't be fixed by retrying, such as 4004 when the
+    /// token is invalid, and aren't retried. Others, such as 4011 when
+    /// sharding is required, indicate that the bot needs to re-shard.
+    GatewayClosed {
+        /// The close code sent by the gateway.
+        code: u16,
+        /// The reason sent by the gateway.
+        reason: String,
+    },
+    /// Discord reported fewer remaining session starts than the number of
+    /// shards to boot, when resolving the autoshard strategy.
+    ///
+    /// Discord limits how many times a bot may identify per day. Booting
+    /// anyway would exhaust the limit partway through, so no shards are
+    /// booted. Refer to [`ShardPlan::session_start_limit`] for more
+    /// information.
+    ///
+    /// [`ShardPlan::session_start_limit`]:
+    /// struct.ShardPlan.html#structfield.session_start_limit
+    InsufficientSessions {
+        /// The number of shards to boot.
+        needed: u64,
+        /// The number of session starts remaining.
+        remaining: u64,
+        /// How long until the limit resets.
+        reset_after: Duration,
+    },
+    /// The values of the sharding strategy are invalid.
+    ///
+    /// This is returned when the start is greater than the end, the end is
+    /// not less than the total, or the total is 0. For a list of shard IDs,
+    /// the start and end are its lowest and highest IDs, and this is also
+    /// returned when the list contains an ID more than once.
+    InvalidStrategy {
+        /// The ID of the last shard to start.
+        end: u64,
+        /// The ID of the first shard to start.
+        start: u64,
+        /// The total number of shards.
+        total: u64,
+    },
+    /// An error from `std::io`, likely when creating a runtime.
+    Io(IoError),
+    /// The environment variable to read the token from is not set, or is not
+    /// valid unicode.
+    ///
+    /// Refer to [`SharderOptions::from_env`] for more information.
+    ///
+    /// [`SharderOptions::from_env`]:
+    /// struct.SharderOptions.html#method.from_env
+    MissingToken {
+        /// The name of the environment variable.
+        var: String,
+    },
+    /// A shard connected, but didn't receive its READY or RESUMED dispatch
+    /// within the ready timeout.
+    ///
+    /// Refer to [`SharderOptions::ready_timeout`] for more information.
+    ///
+    /// [`SharderOptions::ready_timeout`]:
+    /// struct.SharderOptions.html#structfield.ready_timeout
+    ReadyTimeout {
+        /// The ID of the shard being booted.
+        shard_id: u64,
+    },
+    /// An error from the `serenity` crate, such as when requesting the gateway
+    /// information from Discord to resolve the autoshard strategy.
+    ///
+    /// This includes the request being unsuccessful, such as when the token is
+    /// invalid.
     Serenity(SerenityError),
+    /// An error from the `serenity` crate when booting a shard.
+    ShardBoot {
+        /// The ID of the shard being booted.
+        shard_id: u64,
+        /// The error from `serenity`.
+        source: SerenityError,
+    },
+    /// Booting a shard panicked.
+    ///
+    /// The panic is caught and logged, so that a bug doesn't silently stop the
+    /// boot loop.
+    ShardPanicked {
+        /// The ID of the shard being booted.
+        shard_id: u64,
+    },
     /// An error from `tokio::timer`, likely from an issue creating the timer.
     Timer(TimerError),
     /// An error from `tokio::executor` when spawning on the default executor.
     TokioExecutor(ExecutorSpawnError),
+    /// Booting all of the shards did not complete within the total boot
+    /// timeout.
+    ///
+    /// Refer to [`SharderOptions::total_boot_timeout`] for more information.
+    ///
+    /// [`SharderOptions::total_boot_timeout`]:
+    /// struct.SharderOptions.html#structfield.total_boot_timeout
+    TotalBootTimeout {
+        /// The number of shards booted before the timeout elapsed.
+        booted: u64,
+        /// The number of shards planned to be booted.
+        total: u64,
+    },
     /// An error from the `tungstenite` crate.
     Tungstenite(TungsteniteError),
+    /// No shard with the ID is retained by the spawner, such as when
+    /// restarting a shard.
+    ///
+    /// Refer to [`ShardSpawner::restart`] for more information.
+    ///
+    /// [`ShardSpawner::restart`]: struct.ShardSpawner.html#method.restart
+    UnknownShard {
+        /// The ID of the shard.
+        shard_id: u64,
+    },
+    /// The default executor was not able to accept the boot future, likely
+    /// because it is not running.
+    ///
+    /// The boot future is returned alongside the error so that it can be ran
+    /// on another executor instead.
+    Unspawned(ExecutorSpawnError, BootFuture),
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        use self::Error::*;
+
+        match *self {
+            BootTimeout { shard_id } => f.debug_struct("BootTimeout")
+                .field("shard_id", &shard_id)
+                .finish(),
+            Canceled(ref inner) => {
+                f.debug_tuple("Canceled").field(inner).finish()
+            },
+            GatewayClosed { code, ref reason } => {
+                f.debug_struct("GatewayClosed")
+                    .field("code", &code)
+                    .field("reason", reason)
+                    .finish()
+            },
+            InsufficientSessions { needed, remaining, reset_after } => {
+                f.debug_struct("InsufficientSessions")
+                    .field("needed", &needed)
+                    .field("remaining", &remaining)
+                    .field("reset_after", &reset_after)
+                    .finish()
+            },
+            InvalidStrategy { end, start, total } => {
+                f.debug_struct("InvalidStrategy")
+                    .field("end", &end)
+                    .field("start", &start)
+                    .field("total", &total)
+                    .finish()
+            },
+            Io(ref inner) => f.debug_tuple("Io").field(inner).finish(),
+            MissingToken { ref var } => f.debug_struct("MissingToken")
+                .field("var", var)
+                .finish(),
+            ReadyTimeout { shard_id } => f.debug_struct("ReadyTimeout")
+                .field("shard_id", &shard_id)
+                .finish(),
+            Serenity(ref inner) => {
+                f.debug_tuple("Serenity").field(inner).finish()
+            },
+            ShardBoot { shard_id, ref source } => f.debug_struct("ShardBoot")
+                .field("shard_id", &shard_id)
+                .field("source", source)
+                .finish(),
+            ShardPanicked { shard_id } => f.debug_struct("ShardPanicked")
+                .field("shard_id", &shard_id)
+                .finish(),
+            Timer(ref inner) => f.debug_tuple("Timer").field(inner).finish(),
+            TokioExecutor(ref inner) => {
+                f.debug_tuple("TokioExecutor").field(inner).finish()
+            },
+            TotalBootTimeout { booted, total } => {
+                f.debug_struct("TotalBootTimeout")
+                    .field("booted", &booted)
+                    .field("total", &total)
+                    .finish()
+            },
+            Tungstenite(ref inner) => {
+                f.debug_tuple("Tungstenite").field(inner).finish()
+            },
+            UnknownShard { shard_id } => f.debug_struct("UnknownShard")
+                .field("shard_id", &shard_id)
+                .finish(),
+            Unspawned(ref inner, _) => f.debug_tuple("Unspawned")
+                .field(inner)
+                .field(&"Boxed Future of T ()")
+                .finish(),
+        }
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        f.write_str(self.description())
+        use self::Error::*;
+
+        match *self {
+            BootTimeout { shard_id } => {
+                write!(f, "Shard {} timed out while booting", shard_id)
+            },
+            Canceled(ref inner) => write!(f, "Channel canceled: {}", inner),
+            GatewayClosed { code, ref reason } => write!(
+                f,
+                "The gateway closed the connection with code {}: {}",
+                code,
+                reason
+            ),
+            InsufficientSessions { needed, remaining, reset_after } => write!(
+                f,
+                "Booting {} shards needs more than the {} remaining session \
+                 starts, which reset in {}s",
+                needed,
+                remaining,
+                reset_after.as_secs()
+            ),
+            InvalidStrategy { end, start, total } => write!(
+                f,
+                "Invalid sharding strategy: shards {} to {} of {}",
+                start,
+                end,
+                total
+            ),
+            Io(ref inner) => write!(f, "IO error: {}", inner),
+            MissingToken { ref var } => {
+                write!(f, "The token variable {} is not set", var)
+            },
+            ReadyTimeout { shard_id } => {
+                write!(f, "Shard {} timed out waiting to be ready", shard_id)
+            },
+            Serenity(ref inner) => write!(f, "Serenity error: {}", inner),
+            ShardBoot { shard_id, ref source } => {
+                write!(f, "Failed to boot shard {}: {}", shard_id, source)
+            },
+            ShardPanicked { shard_id } => {
+                write!(f, "Shard {} panicked while booting", shard_id)
+            },
+            Timer(ref inner) => write!(f, "Timer error: {}", inner),
+            TokioExecutor(ref inner) => write!(
+                f,
+                "An error occurred while spawning on the executor: {:?}",
+                inner
+            ),
+            TotalBootTimeout { booted, total } => write!(
+                f,
+                "Booting timed out with {} of {} shards booted",
+                booted,
+                total
+            ),
+            Tungstenite(ref inner) => write!(f, "Tungstenite error: {}", inner),
+            UnknownShard { shard_id } => {
+                write!(f, "Shard {} is not retained by the spawner", shard_id)
+            },
+            Unspawned(ref inner, _) => write!(
+                f,
+                "The default executor was not able to spawn the boot future: \
+                 {:?}",
+                inner
+            ),
+        }
+    }
+}
+
+impl Error {
+    /// Returns whether the error is from `std::io`, being [`Io`].
+    ///
+    /// [`Io`]: #variant.Io
+    pub fn is_io(&self) -> bool {
+        match *self {
+            Error::Io(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns whether the error is transient, so that retrying the operation
+    /// that caused it may succeed.
+    ///
+    /// This is useful for supervising the sharder, restarting it on
+    /// recoverable errors and alerting on fatal ones. Errors are classified as:
+    ///
+    /// - [`BootTimeout`]: recoverable
+    /// - [`Canceled`]: fatal, as the sharder is being torn down
+    /// - [`GatewayClosed`]: fatal for close codes 4004 (authentication failed),
+    ///   4010 (invalid shard), 4011 (sharding required), 4012 (invalid API
+    ///   version), 4013 (invalid intents), and 4014 (disallowed intents), and
+    ///   recoverable otherwise
+    /// - [`InsufficientSessions`]: recoverable, once the limit has reset
+    /// - [`InvalidStrategy`]: fatal
+    /// - [`Io`]: recoverable
+    /// - [`MissingToken`]: fatal
+    /// - [`ReadyTimeout`]: recoverable
+    /// - [`Serenity`]: recoverable
+    /// - [`ShardBoot`]: recoverable
+    /// - [`ShardPanicked`]: fatal, as it's caused by a bug
+    /// - [`Timer`]: recoverable
+    /// - [`TokioExecutor`] and [`Unspawned`]: fatal when the executor has shut
+    ///   down, and recoverable otherwise
+    /// - [`TotalBootTimeout`]: recoverable
+    /// - [`Tungstenite`]: recoverable
+    /// - [`UnknownShard`]: fatal
+    ///
+    /// Booting a shard isn't retried when it fails with a fatal error,
+    /// regardless of [`SharderOptions::boot_retries`].
+    ///
+    /// [`BootTimeout`]: #variant.BootTimeout
+    /// [`Canceled`]: #variant.Canceled
+    /// [`GatewayClosed`]: #variant.GatewayClosed
+    /// [`InsufficientSessions`]: #variant.InsufficientSessions
+    /// [`InvalidStrategy`]: #variant.InvalidStrategy
+    /// [`Io`]: #variant.Io
+    /// [`MissingToken`]: #variant.MissingToken
+    /// [`ReadyTimeout`]: #variant.ReadyTimeout
+    /// [`Serenity`]: #variant.Serenity
+    /// [`ShardBoot`]: #variant.ShardBoot
+    /// [`ShardPanicked`]: #variant.ShardPanicked
+    /// [`SharderOptions::boot_retries`]:
+    /// struct.SharderOptions.html#structfield.boot_retries
+    /// [`Timer`]: #variant.Timer
+    /// [`TokioExecutor`]: #variant.TokioExecutor
+    /// [`TotalBootTimeout`]: #variant.TotalBootTimeout
+    /// [`Tungstenite`]: #variant.Tungstenite
+    /// [`UnknownShard`]: #variant.UnknownShard
+    /// [`Unspawned`]: #variant.Unspawned
+    pub fn is_recoverable(&self) -> bool {
+        use self::Error::*;
+
+        match *self {
+            GatewayClosed { code, .. } => match code {
+                4004 | 4010 | 4011 | 4012 | 4013 | 4014 => false,
+                _ => true,
+            },
+            Canceled(_)
+            | InvalidStrategy { .. }
+            | MissingToken { .. }
+            | ShardPanicked { .. }
+            | UnknownShard { .. } => false,
+            TokioExecutor(ref inner) | Unspawned(ref inner, _) => {
+                !inner.is_shutdown()
+            },
+            BootTimeout { .. }
+            | InsufficientSessions { .. }
+            | Io(_)
+            | ReadyTimeout { .. }
+            | Serenity(_)
+            | ShardBoot { .. }
+            | Timer(_)
+            | TotalBootTimeout { .. }
+            | Tungstenite(_) => true,
+        }
+    }
+
+    /// Returns whether the error is from the `serenity` crate, being
+    /// [`Serenity`] or [`ShardBoot`].
+    ///
+    /// [`Serenity`]: #variant.Serenity
+    /// [`ShardBoot`]: #variant.ShardBoot
+    pub fn is_serenity(&self) -> bool {
+        match *self {
+            Error::Serenity(_) | Error::ShardBoot { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Returns whether the error is from `tokio::timer`, being [`Timer`].
+    ///
+    /// [`Timer`]: #variant.Timer
+    pub fn is_timer(&self) -> bool {
+        match *self {
+            Error::Timer(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns whether the error is from the `tungstenite` crate, being
+    /// [`Tungstenite`].
+    ///
+    /// [`Tungstenite`]: #variant.Tungstenite
+    pub fn is_tungstenite(&self) -> bool {
+        match *self {
+            Error::Tungstenite(_) => true,
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        use self::Error::*;
+
+        match (self, other) {
+            (
+                &BootTimeout { shard_id },
+                &BootTimeout { shard_id: shard_id2 },
+            ) => shard_id == shard_id2,
+            (
+                &GatewayClosed { code, ref reason },
+                &GatewayClosed { code: code2, reason: ref reason2 },
+            ) => code == code2 && reason == reason2,
+            (
+                &InsufficientSessions { needed, remaining, reset_after },
+                &InsufficientSessions {
+                    needed: needed2,
+                    remaining: remaining2,
+                    reset_after: reset_after2,
+                },
+            ) => {
+                needed == needed2
+                    && remaining == remaining2
+                    && reset_after == reset_after2
+            },
+            (
+                &InvalidStrategy { end, start, total },
+                &InvalidStrategy { end: end2, start: start2, total: total2 },
+            ) => end == end2 && start == start2 && total == total2,
+            (&MissingToken { ref var }, &MissingToken { var: ref var2 }) => {
+                var == var2
+            },
+            (
+                &ReadyTimeout { shard_id },
+                &ReadyTimeout { shard_id: shard_id2 },
+            ) => shard_id == shard_id2,
+            (
+                &ShardPanicked { shard_id },
+                &ShardPanicked { shard_id: shard_id2 },
+            ) => shard_id == shard_id2,
+            (
+                &TotalBootTimeout { booted, total },
+                &TotalBootTimeout { booted: booted2, total: total2 },
+            ) => booted == booted2 && total == total2,
+            (
+                &UnknownShard { shard_id },
+                &UnknownShard { shard_id: shard_id2 },
+            ) => shard_id == shard_id2,
+            // A cancellation carries no information to compare.
+            (&Canceled(_), &Canceled(_)) => true,
+            // Errors from other crates can't be compared, and comparing their
+            // messages could report unrelated errors as equal.
+            _ => false,
+        }
     }
 }
 
@@ -33,14 +486,67 @@ impl StdError for Error {
         use self::Error::*;
 
         match *self {
+            BootTimeout { .. } => "A shard timed out while booting",
+            Canceled(ref inner) => inner.description(),
+            GatewayClosed { .. } => "The gateway closed the connection",
+            InsufficientSessions { .. } => {
+                "Not enough session starts remain to boot the shards"
+            },
+            InvalidStrategy { .. } => "The sharding strategy is invalid",
+            Io(ref inner) => inner.description(),
+            MissingToken { .. } => "The token variable is not set",
+            ReadyTimeout { .. } => "A shard timed out waiting to be ready",
             Serenity(ref inner) => inner.description(),
+            ShardBoot { ref source, .. } => source.description(),
+            ShardPanicked { .. } => "A shard panicked while booting",
             Timer(ref inner) => inner.description(),
             TokioExecutor(_) => {
                 "An error occurred while spawning on the executor"
             },
+            TotalBootTimeout { .. } => "Booting all shards timed out",
             Tungstenite(ref inner) => inner.description(),
+            UnknownShard { .. } => "The shard is not retained by the spawner",
+            Unspawned(_, _) => {
+                "The default executor was not able to spawn the boot future"
+            },
         }
     }
+
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        use self::Error::*;
+
+        match *self {
+            Canceled(ref inner) => Some(inner),
+            Io(ref inner) => Some(inner),
+            Serenity(ref inner) => Some(inner),
+            ShardBoot { ref source, .. } => Some(source),
+            Timer(ref inner) => Some(inner),
+            Tungstenite(ref inner) => Some(inner),
+            BootTimeout { .. }
+            | GatewayClosed { .. }
+            | InsufficientSessions { .. }
+            | InvalidStrategy { .. }
+            | MissingToken { .. }
+            | ReadyTimeout { .. }
+            | ShardPanicked { .. }
+            | TokioExecutor(_)
+            | TotalBootTimeout { .. }
+            | UnknownShard { .. }
+            | Unspawned(_, _) => None,
+        }
+    }
+}
+
+impl From<Canceled> for Error {
+    fn from(err: Canceled) -> Self {
+        Error::Canceled(err)
+    }
+}
+
+impl From<IoError> for Error {
+    fn from(err: IoError) -> Self {
+        Error::Io(err)
+    }
 }
 
 impl From<SerenityError> for Error {
@@ -66,3 +572,88 @@ impl From<TungsteniteError> for Error {
         Error::Tungstenite(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::{sync::oneshot, Future};
+    use std::{error::Error as StdError, time::Duration};
+    use super::Error;
+
+    #[test]
+    fn test_canceled() {
+        let (tx, rx) = oneshot::channel::<()>();
+        drop(tx);
+
+        let err = rx.map_err(Error::from).wait().unwrap_err();
+
+        assert_eq!(err, Error::Canceled(oneshot::Canceled));
+        assert!(!err.is_recoverable());
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn test_display() {
+        let err = Error::ShardPanicked {
+            shard_id: 3,
+        };
+
+        assert_eq!(err.to_string(), "Shard 3 panicked while booting");
+        assert!(err.source().is_none());
+    }
+
+    #[test]
+    fn test_eq() {
+        let err = Error::BootTimeout {
+            shard_id: 3,
+        };
+
+        assert_eq!(err, Error::BootTimeout { shard_id: 3 });
+        assert_ne!(err, Error::BootTimeout { shard_id: 4 });
+        assert_ne!(err, Error::ShardPanicked { shard_id: 3 });
+
+        // Errors from other crates are never equal.
+        let io = |code| Error::from(::std::io::Error::from_raw_os_error(code));
+
+        assert_ne!(io(1), io(1));
+        assert_ne!(io(1), io(2));
+        assert_ne!(io(1), err);
+    }
+
+    #[test]
+    fn test_is_recoverable() {
+        let closed = |code| Error::GatewayClosed {
+            code,
+            reason: String::new(),
+        };
+
+        assert!(!closed(4004).is_recoverable());
+        assert!(!closed(4011).is_recoverable());
+        assert!(closed(4000).is_recoverable());
+        assert!(Error::BootTimeout { shard_id: 0 }.is_recoverable());
+        assert!(Error::ReadyTimeout { shard_id: 0 }.is_recoverable());
+
+        assert!(Error::InsufficientSessions {
+            needed: 16,
+            remaining: 8,
+            reset_after: Duration::from_secs(60),
+        }.is_recoverable());
+    }
+
+    #[test]
+    fn test_predicates() {
+        let io = Error::from(::std::io::Error::from_raw_os_error(1));
+
+        assert!(io.is_io());
+        assert!(!io.is_serenity());
+        assert!(!io.is_timer());
+        assert!(!Error::BootTimeout { shard_id: 0 }.is_io());
+    }
+
+    #[test]
+    fn test_source() {
+        let err = Error::from(::std::io::Error::from_raw_os_error(1));
+
+        assert!(err.source().is_some());
+        assert!(err.to_string().starts_with("IO error: "));
+    }
+}
@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+/// A structured event emitted while booting shards.
+///
+/// Events are sent to the sender installed via [`SharderOptions::event_sink`]
+/// at the same points as the boot loop's log lines, so that boot progress can
+/// be wired into dashboards without scraping logs.
+///
+/// # Examples
+///
+/// Print the ID of every shard as it boots:
+///
+/// ```rust,no_run
+/// # extern crate futures;
+/// # extern crate serenity_sharder;
+/// # extern crate tokio;
+/// #
+/// # use std::error::Error;
+/// #
+/// # fn main() -> Result<(), Box<Error>> {
+/// #
+/// use futures::{sync::mpsc, Future, Stream};
+/// use serenity_sharder::{self, SharderEvent, SharderOptions};
+/// use std::env;
+///
+/// let (tx, rx) = mpsc::unbounded();
+///
+/// let mut options = SharderOptions::new(env::var("DISCORD_TOKEN")?);
+/// options.event_sink(tx);
+///
+/// let events = rx.for_each(|event| {
+///     if let SharderEvent::ShardBooted { id } = event {
+///         println!("Shard {} booted", id);
+///     }
+///
+///     Ok(())
+/// });
+///
+/// let shards = serenity_sharder::spawn(options)?
+///     .for_each(|_shard| Ok(()))
+///     .map_err(|why| eprintln!("Error booting shards: {}", why));
+///
+/// tokio::run(shards.join(events).map(|_| ()));
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`SharderOptions::event_sink`]:
+/// struct.SharderOptions.html#structfield.event_sink
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SharderEvent {
+    /// A shard is about to be booted.
+    ShardBooting {
+        /// The ID of the shard.
+        id: u64,
+        /// The total number of shards utilized by the bot across all
+        /// instances.
+        total: u64,
+    },
+    /// A shard has booted and been sent to the spawner.
+    ShardBooted {
+        /// The ID of the shard.
+        id: u64,
+    },
+    /// Booting a shard failed, stopping the boot loop.
+    BootFailed {
+        /// The ID of the shard.
+        id: u64,
+        /// The message of the error that caused the failure.
+        error: String,
+    },
+    /// The gateway closed the connection of a shard with close code 4008
+    /// because identifies are being sent too quickly.
+    ///
+    /// This is emitted for every such failure, before waiting to retry if any
+    /// [`SharderOptions::boot_retries`] remain. It's useful for backing off
+    /// other subsystems which identify or send gateway commands.
+    ///
+    /// [`SharderOptions::boot_retries`]:
+    /// struct.SharderOptions.html#structfield.boot_retries
+    RateLimited {
+        /// The ID of the shard.
+        id: u64,
+        /// How long until booting the shard is retried, or `None` if it won't
+        /// be retried.
+        retry_after: Option<Duration>,
+    },
+    /// All shards covered by the sharding strategy have been booted.
+    Completed {
+        /// The number of shards booted.
+        booted: u64,
+        /// How long booting all of the shards took.
+        elapsed: Duration,
+        /// The number of times booting a shard failed and was retried.
+        retries: u64,
+    },
+    #[doc(hidden)]
+    Nonexhaustive,
+}
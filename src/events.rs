@@ -0,0 +1,234 @@
+use futures::{
+    future::{self, Either},
+    stream,
+    Async,
+    Future,
+    Poll,
+    Stream,
+};
+use serenity::{
+    gateway::Shard,
+    model::event::GatewayEvent,
+    Error as SerenityError,
+};
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use {Error, ShardSpawner};
+
+/// The events of a single shard, paired with its ID.
+type EventStream =
+    Box<Stream<Item = (u64, GatewayEvent), Error = Error> + Send>;
+
+/// A stream of the events received by every shard of a [`ShardSpawner`],
+/// paired with the ID of the shard which received them.
+///
+/// This is returned by [`ShardSpawner::events`]. Each shard is driven as it's
+/// spawned: its messages are parsed and processed by the shard, including
+/// waiting for it to reconnect when needed, and the parsed events are
+/// yielded.
+///
+/// An error receiving or processing the messages of a shard is yielded, and
+/// that shard is then dropped, while the other shards keep yielding events.
+/// The stream ends once the spawner has ended and every shard's connection
+/// has ended.
+///
+/// Shards are polled in turn, starting after the shard which yielded the
+/// last event, so that a busy shard can't starve the others.
+///
+/// [`ShardSpawner`]: struct.ShardSpawner.html
+/// [`ShardSpawner::events`]: struct.ShardSpawner.html#method.events
+pub struct ShardEvents {
+    next: usize,
+    shards: Vec<EventStream>,
+    spawner: Option<ShardSpawner>,
+}
+
+impl ShardEvents {
+    pub(crate) fn new(spawner: ShardSpawner) -> Self {
+        Self {
+            next: 0,
+            shards: Vec::new(),
+            spawner: Some(spawner),
+        }
+    }
+
+    /// Polls the spawner for new shards, starting to drive each of them.
+    ///
+    /// Once the spawner has ended it's dropped, and isn't polled again.
+    fn poll_spawner(&mut self) -> Result<(), Error> {
+        loop {
+            let shard = match self.spawner {
+                Some(ref mut spawner) => spawner.poll()?,
+                None => return Ok(()),
+            };
+
+            match shard {
+                Async::Ready(Some(shard)) => self.shards.push(events(shard)),
+                Async::Ready(None) => self.spawner = None,
+                Async::NotReady => return Ok(()),
+            }
+        }
+    }
+}
+
+impl Debug for ShardEvents {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.debug_struct("ShardEvents")
+            .field("next", &self.next)
+            .field("shards", &self.shards.len())
+            .field("spawner", &self.spawner)
+            .finish()
+    }
+}
+
+impl Stream for ShardEvents {
+    type Item = (u64, GatewayEvent);
+    type Error = Error;
+
+    /// Polls the spawner for new shards, and then each shard for its next
+    /// event.
+    ///
+    /// # Logs
+    ///
+    /// [DEBUG] When the connection of a shard has ended.
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        self.poll_spawner()?;
+
+        // Start with the shard after the one which yielded the last event.
+        if !self.shards.is_empty() {
+            let start = self.next % self.shards.len();
+            self.shards.rotate_left(start);
+        }
+
+        self.next = 0;
+        let mut idx = 0;
+
+        while idx < self.shards.len() {
+            match self.shards[idx].poll() {
+                Ok(Async::Ready(Some(event))) => {
+                    self.next = idx + 1;
+
+                    return Ok(Async::Ready(Some(event)));
+                },
+                Ok(Async::Ready(None)) => {
+                    self.shards.swap_remove(idx);
+                },
+                Ok(Async::NotReady) => idx += 1,
+                Err(why) => {
+                    self.shards.swap_remove(idx);
+
+                    return Err(why);
+                },
+            }
+        }
+
+        if self.spawner.is_none() && self.shards.is_empty() {
+            Ok(Async::Ready(None))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+/// Returns a stream of the events of a shard, ending once its connection has
+/// ended.
+fn events(shard: Shard) -> EventStream {
+    let shard_id = shard.shard_info()[0];
+
+    let events = stream::unfold(Some(shard), move |shard| {
+        let shard = shard?;
+
+        Some(next_event(shard).map(move |(event, shard)| match event {
+            Some(event) => (Some((shard_id, event)), Some(shard)),
+            None => {
+                debug!("Connection of shard {} ended", shard_id);
+
+                (None, None)
+            },
+        }))
+    });
+
+    Box::new(events.filter_map(|event| event).map_err(Error::from))
+}
+
+/// Receives the next message of a shard and has the shard process it,
+/// resolving to the parsed event and the shard.
+///
+/// The event is `None` if the connection of the shard has ended.
+pub(crate) fn next_event(
+    mut shard: Shard,
+) -> impl Future<Item = (Option<GatewayEvent>, Shard), Error = SerenityError>
+       + Send {
+    let messages = shard.messages().into_future().map_err(|(why, _)| why);
+
+    messages.and_then(move |(msg, _)| {
+        let msg = match msg {
+            Some(msg) => msg,
+            None => return Either::A(future::ok((None, shard))),
+        };
+        let event = match shard.parse(&msg) {
+            Ok(event) => event,
+            Err((why, _)) => return Either::A(future::err(why)),
+        };
+
+        // The shard processes every event, such as to store its session or
+        // to reconnect.
+        let processed = match shard.process(&event) {
+            Some(future) => Either::A(future.map(|_| ())),
+            None => Either::B(future::ok(())),
+        };
+
+        Either::B(processed.map(move |_| (Some(event), shard)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{stream, Async, Stream};
+    use serenity::model::event::GatewayEvent;
+    use super::{EventStream, ShardEvents};
+    use {Error, ShardSpawner, SharderOptions};
+
+    fn always_ready(shard_id: u64) -> EventStream {
+        let events = (0..).map(move |_| (shard_id, GatewayEvent::HeartbeatAck));
+
+        Box::new(stream::iter_ok(events))
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let mut events = ShardEvents {
+            next: 0,
+            shards: vec![always_ready(0), always_ready(1), always_ready(2)],
+            spawner: None,
+        };
+
+        let shard_ids = (0..6).map(|_| match events.poll() {
+            Ok(Async::Ready(Some((shard_id, _)))) => shard_id,
+            _ => panic!("Expected an event"),
+        }).collect::<Vec<_>>();
+
+        assert_eq!(shard_ids, vec![0, 1, 2, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_spawner_error_ends_stream() {
+        let options = SharderOptions::new("a");
+        let (spawner, tx, _ready) = ShardSpawner::fixture(options);
+        let mut events = spawner.events();
+
+        tx.unbounded_send(Err(Error::ShardPanicked {
+            shard_id: 0,
+        })).unwrap();
+        drop(tx);
+
+        match events.poll() {
+            Err(Error::ShardPanicked { shard_id: 0 }) => {},
+            _ => panic!("Expected the boot loop's error"),
+        }
+
+        match events.poll() {
+            Ok(Async::Ready(None)) => {},
+            _ => panic!("Expected the stream to end"),
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use futures::{future, Future};
+use serde_json::{self, Value};
+use serenity::{
+    http::{request::RequestBuilder, routing::RouteInfo, Client as HttpClient},
+    model::gateway::BotGateway as SerenityBotGateway,
+    Error as SerenityError,
+};
+use std::{sync::Arc, time::Duration};
+use Error;
+
+/// Information retrieved from Discord's Get Gateway Bot endpoint.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct BotGateway {
+    /// The number of shards that may identify at the same time, if included
+    /// in the response.
+    pub max_concurrency: Option<u64>,
+    /// The limit on starting sessions.
+    pub session_start_limit: SessionStartLimit,
+    /// The number of shards Discord recommends the bot uses.
+    pub shards: u64,
+}
+
+impl BotGateway {
+    /// Parses the raw response of the endpoint.
+    ///
+    /// serenity's model doesn't include the max concurrency, so it's read from
+    /// the raw response, and the rest is parsed by serenity.
+    fn from_value(value: Value) -> Result<Self, Error> {
+        let max_concurrency = value
+            .get("session_start_limit")
+            .and_then(|limit| limit.get("max_concurrency"))
+            .and_then(Value::as_u64);
+        let gateway = serde_json::from_value::<SerenityBotGateway>(value)
+            .map_err(SerenityError::Json)?;
+        let limit = &gateway.session_start_limit;
+        // Discord sends the time until the limit resets in milliseconds.
+        let reset_after = Duration::from_millis(limit.reset_after);
+
+        Ok(Self {
+            session_start_limit: SessionStartLimit::new(
+                limit.remaining,
+                reset_after,
+                limit.total,
+            ),
+            shards: gateway.shards,
+            max_concurrency,
+        })
+    }
+}
+
+/// Discord's limit on how many times the bot may identify, as retrieved when
+/// resolving the autoshard strategy.
+///
+/// Every shard booted uses a session start, and the limit is shared by all
+/// instances of the bot. Refer to [`ShardPlan::session_start_limit`] for more
+/// information.
+///
+/// [`ShardPlan::session_start_limit`]:
+/// struct.ShardPlan.html#structfield.session_start_limit
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SessionStartLimit {
+    /// The number of session starts remaining.
+    pub remaining: u64,
+    /// How long until the limit resets.
+    pub reset_after: Duration,
+    /// The total number of session starts allowed per reset.
+    pub total: u64,
+    __nonexhaustive: (),
+}
+
+impl SessionStartLimit {
+    pub(crate) fn new(
+        remaining: u64,
+        reset_after: Duration,
+        total: u64,
+    ) -> Self {
+        Self {
+            remaining,
+            reset_after,
+            total,
+            __nonexhaustive: (),
+        }
+    }
+}
+
+/// Retrieves information about the gateway for the bot with the given token,
+/// using serenity's HTTP client.
+///
+/// The given client is used if any, otherwise a new one is built for the
+/// token.
+pub(crate) fn bot(
+    token: &str,
+    client: Option<Arc<HttpClient>>,
+) -> impl Future<Item = BotGateway, Error = Error> + Send {
+    future::result(http_client(token, client)).and_then(|client| {
+        let request = RequestBuilder::new(RouteInfo::GetBotGateway).build();
+
+        client.fire::<Value>(request).from_err()
+    }).and_then(|value| {
+        let gateway = BotGateway::from_value(value)?;
+
+        debug!(
+            "Retrieved gateway information: shards={}, max_concurrency={:?}, \
+             session_start_limit={:?}",
+            gateway.shards,
+            gateway.max_concurrency,
+            gateway.session_start_limit
+        );
+
+        Ok(gateway)
+    })
+}
+
+/// Returns the given HTTP client, or builds one for the given token if there
+/// is none.
+fn http_client(
+    token: &str,
+    client: Option<Arc<HttpClient>>,
+) -> Result<Arc<HttpClient>, Error> {
+    if let Some(client) = client {
+        return Ok(client);
+    }
+
+    Ok(Arc::new(HttpClient::new_with_token(token)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+    use std::time::Duration;
+    use super::{BotGateway, SessionStartLimit};
+    use Error;
+
+    fn value(json: &str) -> Value {
+        json.parse().unwrap()
+    }
+
+    #[test]
+    fn test_from_value() {
+        let gateway = value(r#"{
+            "url": "wss://gateway.discord.gg",
+            "shards": 16,
+            "session_start_limit": {
+                "total": 1000,
+                "remaining": 900,
+                "reset_after": 14400000,
+                "max_concurrency": 4
+            }
+        }"#);
+
+        assert_eq!(BotGateway::from_value(gateway).unwrap(), BotGateway {
+            max_concurrency: Some(4),
+            session_start_limit: SessionStartLimit::new(
+                900,
+                Duration::from_secs(14400),
+                1000,
+            ),
+            shards: 16,
+        });
+    }
+
+    #[test]
+    fn test_from_value_invalid() {
+        let gateway = value(r#"{"code": 0, "message": "401: Unauthorized"}"#);
+
+        match BotGateway::from_value(gateway) {
+            Err(Error::Serenity(_)) => {},
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_from_value_missing_max_concurrency() {
+        let gateway = value(r#"{
+            "url": "wss://gateway.discord.gg",
+            "shards": 1,
+            "session_start_limit": {
+                "total": 1000,
+                "remaining": 1000,
+                "reset_after": 0
+            }
+        }"#);
+
+        let gateway = BotGateway::from_value(gateway).unwrap();
+        assert_eq!(gateway.max_concurrency, None);
+        assert_eq!(gateway.shards, 1);
+    }
+}
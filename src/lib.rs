@@ -24,7 +24,7 @@
 //! #
 //! # fn main() -> Result<(), Box<Error>> {
 //! #
-//! use futures::Stream;
+//! use futures::{Future, Stream};
 //! use serenity_sharder::{self, SharderOptions, ShardingStrategy};
 //! use std::env;
 //!
@@ -36,7 +36,7 @@
 //!     println!("Shard spawned! {:?}", shard.shard_info());
 //!
 //!     Ok(())
-//! });
+//! }).map_err(|why| eprintln!("Error booting shards: {}", why));
 //!
 //! tokio::run(future);
 //! #
@@ -49,6 +49,10 @@
 #![deny(missing_docs, unknown_lints)]
 
 extern crate futures;
+extern crate rand;
+#[cfg(feature = "serde")]
+extern crate serde;
+extern crate serde_json;
 extern crate serenity;
 extern crate tokio;
 extern crate tungstenite;
@@ -58,18 +62,50 @@ extern crate log;
 
 pub mod prelude;
 
+mod blocking;
+mod bucket;
+mod cancel;
+mod channel;
+mod coordinator;
 mod error;
+mod event;
+mod events;
+mod gateway;
+mod metrics;
 mod options;
+mod order;
+mod plan;
+mod queue;
+mod ready;
 mod spawn;
 mod spawner;
 mod strategy;
+mod timeout;
 
 pub use self::{
+    blocking::boot_blocking,
+    bucket::IdentifyBucket,
+    cancel::{CancellationToken, Cancelled},
+    coordinator::Coordinator,
     error::Error,
+    event::SharderEvent,
+    events::ShardEvents,
+    gateway::SessionStartLimit,
+    metrics::SharderMetrics,
     options::SharderOptions,
-    spawn::spawn,
+    order::BootOrder,
+    plan::{
+        plan,
+        recommended_shards,
+        recommended_shards_with_client,
+        RecommendedShards,
+        ShardPlan,
+    },
+    ready::AllReady,
+    spawn::{spawn, spawn_on, spawn_with_handle},
     spawner::ShardSpawner,
     strategy::ShardingStrategy,
+    timeout::TakeUntilTimeout,
 };
 
 use futures::Future;
@@ -78,6 +114,24 @@ use serenity::{
     model::event::GatewayEvent,
 };
 
+/// The future which boots shards and sends them to a [`ShardSpawner`].
+///
+/// This is normally spawned on the default executor by [`spawn`], but is
+/// returned in [`Error::Unspawned`] if the default executor was not able to
+/// accept it, so that it can be ran on another executor.
+///
+/// [`Error::Unspawned`]: enum.Error.html#variant.Unspawned
+/// [`ShardSpawner`]: struct.ShardSpawner.html
+/// [`spawn`]: fn.spawn.html
+pub type BootFuture = Box<Future<Item = (), Error = ()> + Send>;
+
+/// A future which stops sharding once it resolves, set via
+/// [`SharderOptions::shutdown_signal`].
+///
+/// [`SharderOptions::shutdown_signal`]:
+/// struct.SharderOptions.html#method.shutdown_signal
+pub type ShutdownSignal = Box<Future<Item = (), Error = ()> + Send>;
+
 /// The return type of a closure after having a serenity shard process an event.
 ///
 /// This is not necessarily _always_ the return type, but often it will be, and
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+/// Callbacks for recording metrics about booting shards.
+///
+/// Install an implementation via [`SharderOptions::metrics`] to record the
+/// boot loop's progress with any metrics backend, such as a histogram of how
+/// long each shard takes to boot. Every method does nothing by default, so
+/// only the needed ones have to be implemented.
+///
+/// # Examples
+///
+/// Print how long each shard took to boot:
+///
+/// ```rust,no_run
+/// # extern crate serenity_sharder;
+/// #
+/// # use std::error::Error;
+/// #
+/// # fn main() -> Result<(), Box<Error>> {
+/// #
+/// use serenity_sharder::{SharderMetrics, SharderOptions};
+/// use std::{env, sync::Arc, time::Duration};
+///
+/// struct PrintMetrics;
+///
+/// impl SharderMetrics for PrintMetrics {
+///     fn on_boot_end(&self, shard_id: u64, duration: Duration) {
+///         println!("Shard {} booted in {:?}", shard_id, duration);
+///     }
+/// }
+///
+/// let mut options = SharderOptions::new(env::var("DISCORD_TOKEN")?);
+/// options.metrics(Arc::new(PrintMetrics));
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`SharderOptions::metrics`]: struct.SharderOptions.html#structfield.metrics
+pub trait SharderMetrics: Send + Sync {
+    /// Called when a shard starts booting.
+    fn on_boot_start(&self, _shard_id: u64) {}
+
+    /// Called when a shard has booted and been sent to the spawner, with how
+    /// long booting it took, including any retries.
+    fn on_boot_end(&self, _shard_id: u64, _duration: Duration) {}
+
+    /// Called when booting a shard failed.
+    fn on_boot_error(&self, _shard_id: u64) {}
+}
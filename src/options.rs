@@ -1,4 +1,29 @@
-use ShardingStrategy;
+use futures::{future::Shared, sync::mpsc::UnboundedSender, Future};
+use gateway;
+use rand::{self, Rng};
+use serenity::{
+    http::Client as HttpClient,
+    model::{gateway::Game, user::OnlineStatus},
+};
+#[cfg(feature = "dangerous_identify_override")]
+use std::collections::HashMap;
+use std::{
+    env,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    sync::Arc,
+    time::Duration,
+};
+use {
+    BootOrder,
+    CancellationToken,
+    Coordinator,
+    Error,
+    IdentifyBucket,
+    SharderEvent,
+    SharderMetrics,
+    ShardingStrategy,
+    ShutdownSignal,
+};
 
 /// Options to use when creating a new sharder.
 ///
@@ -9,24 +34,368 @@ use ShardingStrategy;
 ///
 /// [`spawn`]: fn.spawn.html
 /// [`token`]: #structfield.token
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct SharderOptions {
-    /// The number of seconds to wait between each shard start.
+    /// The base duration of the backoff between attempts to boot a shard.
     ///
-    /// This must be at least 5.
+    /// After the `n`th failed attempt, the next attempt is made after
+    /// `boot_backoff_base * 2^n`, plus a random jitter of up to half of that
+    /// again.
     ///
-    /// Defaults to 6.
-    pub delay: u64,
+    /// Defaults to 1 second.
+    pub boot_backoff_base: Duration,
+    /// The order to boot the shards of the [`strategy`] in.
+    ///
+    /// Any [`priority_shards`] are still booted first. Refer to [`BootOrder`]
+    /// for more information.
+    ///
+    /// Defaults to `None`, using the order of the strategy: ascending for
+    /// [`ShardingStrategy::Range`], and the order of the list for
+    /// [`ShardingStrategy::List`].
+    ///
+    /// [`BootOrder`]: enum.BootOrder.html
+    /// [`ShardingStrategy::List`]: enum.ShardingStrategy.html#variant.List
+    /// [`ShardingStrategy::Range`]: enum.ShardingStrategy.html#variant.Range
+    /// [`priority_shards`]: #structfield.priority_shards
+    /// [`strategy`]: #structfield.strategy
+    pub boot_order: Option<BootOrder>,
+    /// The number of times to retry booting a shard that failed to boot, such
+    /// as due to a transient error from the gateway.
+    ///
+    /// Retries are delayed according to the [`boot_backoff_base`]. Once all
+    /// retries are exhausted, the error stops the boot loop.
+    ///
+    /// Defaults to 0, meaning shards are not retried.
+    ///
+    /// [`boot_backoff_base`]: #structfield.boot_backoff_base
+    pub boot_retries: u32,
+    /// The maximum duration to wait for a shard to boot.
+    ///
+    /// If a shard doesn't boot in time, such as when the gateway hangs during
+    /// IDENTIFY, the attempt is abandoned. It's then retried if any
+    /// [`boot_retries`] remain, or otherwise fails with
+    /// [`Error::BootTimeout`], stopping the boot loop.
+    ///
+    /// Defaults to `None`, waiting indefinitely.
+    ///
+    /// [`Error::BootTimeout`]: enum.Error.html#variant.BootTimeout
+    /// [`boot_retries`]: #structfield.boot_retries
+    pub boot_timeout: Option<Duration>,
+    /// The token which stops the boot loop once cancelled.
+    ///
+    /// Refer to [`CancellationToken`] for more information.
+    ///
+    /// Defaults to a new token. Note that clones of the options share the
+    /// same token.
+    ///
+    /// [`CancellationToken`]: struct.CancellationToken.html
+    pub cancellation_token: CancellationToken,
+    /// The maximum number of booted shards that may be waiting to be received
+    /// by the [`ShardSpawner`].
+    ///
+    /// When set, shards are sent over a channel bounded to this capacity. Once
+    /// it's full, the boot loop waits for the spawner to be polled before
+    /// sending the next shard, and doesn't boot the next round of shards
+    /// until every shard of the current round has been sent. This paces
+    /// booting to consumption, so that booted shards don't pile up in memory
+    /// when the consumer is slow. A capacity of `0` is treated as `1`.
+    ///
+    /// Defaults to `None`, sending shards over an unbounded channel regardless
+    /// of whether they've been received.
+    ///
+    /// [`ShardSpawner`]: struct.ShardSpawner.html
+    pub channel_capacity: Option<u64>,
+    /// The hook to wait on before each identify, coordinating identifies with
+    /// sharders in other processes.
+    ///
+    /// Refer to [`Coordinator`] for more information.
+    ///
+    /// Defaults to `None`, only coordinating identifies within this process.
+    ///
+    /// [`Coordinator`]: trait.Coordinator.html
+    pub coordinator: Option<Arc<Coordinator>>,
+    /// The duration to wait between each shard start.
+    ///
+    /// This must be at least the [`min_delay`]. This is not used if a
+    /// [`delay_fn`] is set.
+    ///
+    /// Defaults to 6 seconds.
+    ///
+    /// [`delay_fn`]: #structfield.delay_fn
+    /// [`min_delay`]: #structfield.min_delay
+    pub delay: Duration,
+    /// A function given the ID of a booted shard, returning the delay to wait
+    /// before booting the next shard.
+    ///
+    /// This allows, for example, the first few shards to boot quickly and
+    /// later ones to wait longer. When booting multiple shards at a time per
+    /// [`max_concurrency`], the longest delay of the shards in the round is
+    /// used. Delays below the [`min_delay`] are increased to it.
+    ///
+    /// Setting a constant [`delay`] via its builder installs a function
+    /// returning that delay.
+    ///
+    /// Defaults to `None`, using the [`delay`].
+    ///
+    /// [`delay`]: #structfield.delay
+    /// [`max_concurrency`]: #structfield.max_concurrency
+    /// [`min_delay`]: #structfield.min_delay
+    pub delay_fn: Option<Arc<Fn(u64) -> Duration + Send + Sync>>,
+    /// The maximum random amount to add to or subtract from the delay before
+    /// each round of boots.
+    ///
+    /// This spreads out the boots of multiple instances of a bot that were
+    /// restarted at the same time, such as after a deploy, so that they don't
+    /// identify in lockstep. Jittered delays are never below the
+    /// [`min_delay`].
+    ///
+    /// Defaults to 0, using the delay exactly.
+    ///
+    /// [`min_delay`]: #structfield.min_delay
+    pub delay_jitter: Duration,
+    /// The sender to emit structured boot events to.
+    ///
+    /// Refer to [`SharderEvent`] for more information. If the receiver is
+    /// dropped, events are discarded.
+    ///
+    /// Defaults to `None`, emitting no events.
+    ///
+    /// [`SharderEvent`]: enum.SharderEvent.html
+    pub event_sink: Option<UnboundedSender<SharderEvent>>,
+    /// Whether to boot shards without any delay between them, ignoring the
+    /// [`delay`], [`delay_fn`], and [`delay_jitter`].
+    ///
+    /// **This will get you rate limited in production**: Discord requires at
+    /// least 5 seconds between identifies. It is intended for local
+    /// development, such as when testing against a small number of shards.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`delay`]: #structfield.delay
+    /// [`delay_fn`]: #structfield.delay_fn
+    /// [`delay_jitter`]: #structfield.delay_jitter
+    pub fast_boot: bool,
+    /// The HTTP client to use when retrieving gateway information from
+    /// Discord, such as when resolving [`ShardingStrategy::Autoshard`].
+    ///
+    /// This allows reusing serenity's client, and its connection pool and
+    /// ratelimiting, from the rest of the bot. Requests are made with the
+    /// client's own token.
+    ///
+    /// Defaults to `None`, building a new client for [`token`] when needed.
+    ///
+    /// [`ShardingStrategy::Autoshard`]:
+    /// enum.ShardingStrategy.html#variant.Autoshard
+    /// [`token`]: #structfield.token
+    pub http_client: Option<Arc<HttpClient>>,
+    /// The bucket to acquire a permit from before each identify, shared with
+    /// other sharders in the same process.
+    ///
+    /// Refer to [`IdentifyBucket`] for more information.
+    ///
+    /// Defaults to `None`, only spacing out identifies within this sharder.
+    ///
+    /// [`IdentifyBucket`]: struct.IdentifyBucket.html
+    pub identify_bucket: Option<Arc<IdentifyBucket>>,
+    /// Overrides of the `[id, total]` shard array sent when identifying,
+    /// keyed by the ID of the shard being booted.
+    ///
+    /// **This is dangerous**: sending an array that doesn't match the sharding
+    /// strategy can cause session conflicts or events to be received by the
+    /// wrong shard. It is only available with the
+    /// `dangerous_identify_override` feature, and is intended for reproducing
+    /// gateway behaviour in tests.
+    ///
+    /// Defaults to no overrides, using the array derived from the
+    /// [`strategy`].
+    ///
+    /// [`strategy`]: #structfield.strategy
+    #[cfg(feature = "dangerous_identify_override")]
+    pub identify_overrides: HashMap<u64, [u64; 2]>,
+    /// The maximum number of shards that may identify at the same time.
+    ///
+    /// Shards are grouped into buckets by `shard_id % max_concurrency`. Each
+    /// round of booting starts up to one shard from each bucket at the same
+    /// time, and the [`delay`] is applied between rounds rather than between
+    /// individual shards. This can greatly reduce the startup time of large
+    /// bots.
+    ///
+    /// When this is `None` and the [`strategy`] is
+    /// [`ShardingStrategy::Autoshard`], the value provided by Discord is used,
+    /// or 1 if Discord didn't provide one. Otherwise, `None` means shards are
+    /// booted one at a time.
+    ///
+    /// Defaults to `None`.
+    ///
+    /// [`ShardingStrategy::Autoshard`]:
+    /// enum.ShardingStrategy.html#variant.Autoshard
+    /// [`delay`]: #structfield.delay
+    /// [`strategy`]: #structfield.strategy
+    pub max_concurrency: Option<u64>,
+    /// The maximum number of shards to use when the [`strategy`] is
+    /// [`ShardingStrategy::Autoshard`].
+    ///
+    /// If Discord recommends more shards than this, the maximum is used
+    /// instead. This protects against over-provisioning on a machine with
+    /// fixed capacity when Discord raises its recommendation.
+    ///
+    /// Defaults to `None`, using the number of shards recommended by Discord.
+    ///
+    /// [`ShardingStrategy::Autoshard`]:
+    /// enum.ShardingStrategy.html#variant.Autoshard
+    /// [`strategy`]: #structfield.strategy
+    pub max_shards: Option<u64>,
+    /// The callbacks to record metrics about booting shards with.
+    ///
+    /// Refer to [`SharderMetrics`] for more information.
+    ///
+    /// Defaults to `None`, recording no metrics.
+    ///
+    /// [`SharderMetrics`]: trait.SharderMetrics.html
+    pub metrics: Option<Arc<SharderMetrics>>,
+    /// The minimum number of seconds to wait between each round of boots.
+    ///
+    /// Delays below this are increased to it. Discord requires at least 5
+    /// seconds between identifies in the same concurrency bucket, but this can
+    /// be lowered when identifies are otherwise rate limited, such as by a
+    /// shared [`IdentifyBucket`].
+    ///
+    /// Defaults to [`MIN_DELAY`].
+    ///
+    /// [`IdentifyBucket`]: struct.IdentifyBucket.html
+    /// [`MIN_DELAY`]: #associatedconstant.MIN_DELAY
+    pub min_delay: u64,
+    /// A function called with the ID of a shard right before it starts
+    /// booting, such as to register it in service discovery.
+    ///
+    /// This is called once per boot of a shard, before its first identify,
+    /// and not again for retries or for reconnects made by the shard.
+    ///
+    /// Defaults to `None`.
+    pub on_shard_start: Option<Arc<Fn(u64) + Send + Sync>>,
+    /// A function called with the ID of a shard once the sharder has torn it
+    /// down, pairing with [`on_shard_start`].
+    ///
+    /// This is called once when booting a shard fails or is abandoned by the
+    /// boot loop stopping, when a booted shard is dropped because the
+    /// [`ShardSpawner`] was dropped, and for each shard closed by
+    /// [`ShardSpawner::shutdown`]. Shards yielded by the spawner are owned by
+    /// you, so this isn't called when they're dropped.
+    ///
+    /// Defaults to `None`.
+    ///
+    /// [`ShardSpawner`]: struct.ShardSpawner.html
+    /// [`ShardSpawner::shutdown`]: struct.ShardSpawner.html#method.shutdown
+    /// [`on_shard_start`]: #structfield.on_shard_start
+    pub on_shard_stop: Option<Arc<Fn(u64) + Send + Sync>>,
+    /// The presence to set on every shard once it has booted: the game being
+    /// played, if any, and the online status.
+    ///
+    /// This applies to every shard booted under any [`strategy`], and is sent
+    /// as a presence update right after the shard has identified, before it's
+    /// yielded by the [`ShardSpawner`].
+    ///
+    /// Defaults to `None`, leaving the presence unset.
+    ///
+    /// [`ShardSpawner`]: struct.ShardSpawner.html
+    /// [`strategy`]: #structfield.strategy
+    pub presence: Option<(Option<Game>, OnlineStatus)>,
+    /// The IDs of shards to boot before all others, in order.
+    ///
+    /// This is useful for booting the shards hosting the largest guilds, which
+    /// take the longest to become available, first during a rollout. Shards
+    /// not in this list are booted afterwards in their normal order, and IDs
+    /// not covered by the [`strategy`] are ignored.
+    ///
+    /// This only changes the order in which shards are booted: each boot is
+    /// still rate limited by the [`delay`], so prioritized shards do not
+    /// boot any faster than they otherwise would. When booting multiple
+    /// shards at a time per [`max_concurrency`], a round ends early at a
+    /// prioritized shard in the same bucket as one already in the round,
+    /// so that the order is preserved.
+    ///
+    /// Defaults to no priority shards.
+    ///
+    /// [`delay`]: #structfield.delay
+    /// [`max_concurrency`]: #structfield.max_concurrency
+    /// [`strategy`]: #structfield.strategy
+    pub priority_shards: Vec<u64>,
+    /// The maximum duration to wait for a connected shard to receive its
+    /// READY or RESUMED dispatch.
+    ///
+    /// Shards are only sent to the [`ShardSpawner`] once ready, so this stops
+    /// a shard which is stuck mid-handshake from hanging the boot loop. The
+    /// attempt is then retried if any [`boot_retries`] remain, or otherwise
+    /// fails with [`Error::ReadyTimeout`], stopping the boot loop.
+    ///
+    /// Defaults to 30 seconds. Set this to `None` to wait indefinitely.
+    ///
+    /// [`Error::ReadyTimeout`]: enum.Error.html#variant.ReadyTimeout
+    /// [`ShardSpawner`]: struct.ShardSpawner.html
+    /// [`boot_retries`]: #structfield.boot_retries
+    pub ready_timeout: Option<Duration>,
+    /// Whether the [`ShardSpawner`] retains spawned shards, so that they can
+    /// be retrieved by ID after booting.
+    ///
+    /// When enabled, spawned shards are stored in the spawner as it's polled
+    /// instead of being yielded by it, and can be accessed via
+    /// [`ShardSpawner::get`] and [`ShardSpawner::shards`]. The spawner must
+    /// still be polled to completion for shards to be retained.
+    ///
+    /// Shards are not cheaply cloneable, so this is opt-in to avoid the memory
+    /// cost for users who only consume the stream once.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`ShardSpawner`]: struct.ShardSpawner.html
+    /// [`ShardSpawner::get`]: struct.ShardSpawner.html#method.get
+    /// [`ShardSpawner::shards`]: struct.ShardSpawner.html#method.shards
+    pub retain_shards: bool,
+    /// A future which cancels the [`cancellation_token`] once it resolves,
+    /// such as a ctrl-c signal.
+    ///
+    /// This composes with a shutdown future that an application already has.
+    /// Once the signal resolves or fails, no further shards are booted and the
+    /// [`ShardSpawner`] stream ends. Refer to [`CancellationToken`] for more
+    /// information.
+    ///
+    /// Defaults to `None`.
+    ///
+    /// [`CancellationToken`]: struct.CancellationToken.html
+    /// [`ShardSpawner`]: struct.ShardSpawner.html
+    /// [`cancellation_token`]: #structfield.cancellation_token
+    pub shutdown_signal: Option<Shared<ShutdownSignal>>,
     /// The strategy to use for sharding.
     ///
     /// Defaults to [`ShardingStrategy::Autoshard`].
     pub strategy: ShardingStrategy,
     /// The bot's token.
     pub token: String,
+    /// The maximum duration to wait for all of the shards to boot.
+    ///
+    /// Unlike the [`boot_timeout`], which applies to each shard, this applies
+    /// to the whole boot loop. This catches a fleet which never finishes
+    /// booting, such as when many shards each take just under the boot
+    /// timeout, and is useful for health-gated deploys. Once it elapses, the
+    /// boot loop stops and fails with [`Error::TotalBootTimeout`]. When the
+    /// error reaches the [`ShardSpawner`], it closes the shards it still holds
+    /// before yielding the error.
+    ///
+    /// Defaults to `None`, waiting indefinitely.
+    ///
+    /// [`Error::TotalBootTimeout`]: enum.Error.html#variant.TotalBootTimeout
+    /// [`ShardSpawner`]: struct.ShardSpawner.html
+    /// [`boot_timeout`]: #structfield.boot_timeout
+    pub total_boot_timeout: Option<Duration>,
     __nonexhaustive: (),
 }
 
 impl SharderOptions {
+    /// The default [`min_delay`], in seconds.
+    ///
+    /// [`min_delay`]: #structfield.min_delay
+    pub const MIN_DELAY: u64 = 5;
+
     /// Creates a new set of options for spawning shards.
     ///
     /// # Examples
@@ -58,20 +427,77 @@ impl SharderOptions {
 
     fn _new(token: String) -> Self {
         Self {
-            delay: 6,
+            boot_backoff_base: Duration::from_secs(1),
+            boot_order: None,
+            boot_retries: 0,
+            boot_timeout: None,
+            cancellation_token: CancellationToken::new(),
+            channel_capacity: None,
+            coordinator: None,
+            delay: Duration::from_secs(6),
+            delay_fn: None,
+            delay_jitter: Duration::from_secs(0),
+            event_sink: None,
+            fast_boot: false,
+            http_client: None,
+            identify_bucket: None,
+            #[cfg(feature = "dangerous_identify_override")]
+            identify_overrides: HashMap::new(),
+            max_concurrency: None,
+            max_shards: None,
+            metrics: None,
+            min_delay: Self::MIN_DELAY,
+            on_shard_start: None,
+            on_shard_stop: None,
+            presence: None,
+            priority_shards: Vec::new(),
+            ready_timeout: Some(Duration::from_secs(30)),
+            retain_shards: false,
+            shutdown_signal: None,
             strategy: ShardingStrategy::Autoshard,
+            total_boot_timeout: None,
             __nonexhaustive: (),
             token,
         }
     }
 
-    /// Sets the delay between shard starts.
+    /// Creates a new set of options for spawning shards, reading the token
+    /// from the `DISCORD_TOKEN` environment variable.
     ///
-    /// Refer to [`delay`] for more information.
+    /// Refer to [`from_env_var`] to read the token from another variable.
     ///
     /// # Examples
     ///
-    /// Set a delay of 10 seconds:
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::{self, SharderOptions};
+    ///
+    /// let spawner = serenity_sharder::spawn(SharderOptions::from_env()?)?;
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MissingToken`] when the variable is not set or is not
+    /// valid unicode.
+    ///
+    /// [`Error::MissingToken`]: enum.Error.html#variant.MissingToken
+    /// [`from_env_var`]: #method.from_env_var
+    pub fn from_env() -> Result<Self, Error> {
+        Self::from_env_var("DISCORD_TOKEN")
+    }
+
+    /// Creates a new set of options for spawning shards, reading the token
+    /// from the environment variable with the given name.
+    ///
+    /// # Examples
     ///
     /// ```rust,no_run
     /// # extern crate serenity_sharder;
@@ -81,41 +507,85 @@ impl SharderOptions {
     /// # fn main() -> Result<(), Box<Error>> {
     /// #
     /// use serenity_sharder::SharderOptions;
-    /// use std::env;
     ///
-    /// let token = env::var("DISCORD_TOKEN")?;
-    /// let mut options = SharderOptions::new(token);
-    /// options.delay(10);
+    /// let options = SharderOptions::from_env_var("BOT_TOKEN")?;
     /// #
     /// #     Ok(())
     /// # }
     /// ```
     ///
-    /// # Logs
+    /// # Errors
     ///
-    /// [WARN] When the sharding delay was below the minimum and was
-    /// automatically increased.
+    /// Returns [`Error::MissingToken`] when the variable is not set or is not
+    /// valid unicode.
     ///
-    /// [`delay`]: #structfield.delay
-    pub fn delay(&mut self, delay: u64) -> &mut Self {
-        self.delay = if delay >= 5 {
-            delay
-        } else {
-            warn!("Increased sharding delay from {} to 5", delay);
+    /// [`Error::MissingToken`]: enum.Error.html#variant.MissingToken
+    pub fn from_env_var(var: impl AsRef<str>) -> Result<Self, Error> {
+        let var = var.as_ref();
 
-            5
-        };
+        match env::var(var) {
+            Ok(token) => Ok(Self::_new(token)),
+            Err(_) => Err(Error::MissingToken {
+                var: var.to_owned(),
+            }),
+        }
+    }
+
+    /// Sets the base duration of the backoff between attempts to boot a shard.
+    ///
+    /// Refer to [`boot_backoff_base`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Retry booting shards up to 3 times, starting with a backoff of 500
+    /// milliseconds:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::SharderOptions;
+    /// use std::{env, time::Duration};
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options
+    ///     .boot_retries(3)
+    ///     .boot_backoff_base(Duration::from_millis(500));
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`boot_backoff_base`]: #structfield.boot_backoff_base
+    pub fn boot_backoff_base(&mut self, base: Duration) -> &mut Self {
+        self.boot_backoff_base = base;
 
         self
     }
 
-    /// Sets the sharding strategy to use for starting shards.
+    /// Returns the backoff to wait after the given zero-indexed attempt to
+    /// boot a shard failed, including jitter.
+    pub(crate) fn boot_backoff(&self, attempt: u32) -> Duration {
+        // Cap the exponent so that the multiplication can't overflow.
+        let backoff = self.boot_backoff_base * 2u32.pow(attempt.min(16));
+        let millis = backoff.as_secs() * 1000
+            + u64::from(backoff.subsec_millis());
+        let jitter = rand::thread_rng().gen_range(0, millis / 2 + 1);
+
+        backoff + Duration::from_millis(jitter)
+    }
+
+    /// Sets the order to boot the shards of the strategy in.
     ///
-    /// Refer to [`strategy`] for more information.
+    /// Refer to [`boot_order`] for more information.
     ///
     /// # Examples
     ///
-    /// Set the sharding strategy to use a range of shards 0 to 19 of 40:
+    /// Boot shards 0 to 9 of 10 from the highest ID to the lowest:
     ///
     /// ```rust,no_run
     /// # extern crate serenity_sharder;
@@ -124,31 +594,33 @@ impl SharderOptions {
     /// #
     /// # fn main() -> Result<(), Box<Error>> {
     /// #
-    /// use serenity_sharder::{SharderOptions, ShardingStrategy};
+    /// use serenity_sharder::{BootOrder, SharderOptions, ShardingStrategy};
     /// use std::env;
     ///
     /// let token = env::var("DISCORD_TOKEN")?;
     /// let mut options = SharderOptions::new(token);
-    /// options.strategy(ShardingStrategy::Range(0, 19, 40));
+    /// options
+    ///     .strategy(ShardingStrategy::Range(0, 9, 10))
+    ///     .boot_order(BootOrder::Descending);
     /// #
     /// #     Ok(())
     /// # }
     /// ```
     ///
-    /// [`strategy`]: #structfield.strategy
-    pub fn strategy(&mut self, strategy: ShardingStrategy) -> &mut Self {
-        self.strategy = strategy;
+    /// [`boot_order`]: #structfield.boot_order
+    pub fn boot_order(&mut self, boot_order: BootOrder) -> &mut Self {
+        self.boot_order = Some(boot_order);
 
         self
     }
 
-    /// Sets the bot's token.
+    /// Sets the number of times to retry booting a shard that failed to boot.
     ///
-    /// Refer to [`token`] for more information.
+    /// Refer to [`boot_retries`] for more information.
     ///
     /// # Examples
     ///
-    /// Set the token to a new token after-the-fact:
+    /// Retry booting shards up to 3 times:
     ///
     /// ```rust,no_run
     /// # extern crate serenity_sharder;
@@ -162,66 +634,1756 @@ impl SharderOptions {
     ///
     /// let token = env::var("DISCORD_TOKEN")?;
     /// let mut options = SharderOptions::new(token);
-    ///
-    /// // work here
-    /// # let use_alternative_token = true;
-    /// if use_alternative_token {
-    ///     options.token(env::var("ALTERNATE_TOKEN")?);
-    /// }
+    /// options.boot_retries(3);
     /// #
     /// #     Ok(())
     /// # }
     /// ```
     ///
-    /// [`token`]: #structfield.token
-    #[inline]
-    pub fn token(&mut self, token: impl ToString) -> &mut Self {
-        self._token(token.to_string())
-    }
-
-    fn _token(&mut self, token: String) -> &mut Self {
-        self.token = token;
+    /// [`boot_retries`]: #structfield.boot_retries
+    pub fn boot_retries(&mut self, retries: u32) -> &mut Self {
+        self.boot_retries = retries;
 
         self
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use {SharderOptions, ShardingStrategy};
 
-    #[test]
-    fn test_fields() {
-        let mut options = SharderOptions::new("aaa");
-        options.delay(7).strategy(ShardingStrategy::Autoshard);
+    /// Sets the maximum duration to wait for a shard to boot.
+    ///
+    /// Refer to [`boot_timeout`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Abandon boots taking longer than 30 seconds, retrying them up to twice:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::SharderOptions;
+    /// use std::{env, time::Duration};
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options
+    ///     .boot_timeout(Duration::from_secs(30))
+    ///     .boot_retries(2);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`boot_timeout`]: #structfield.boot_timeout
+    pub fn boot_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.boot_timeout = Some(timeout);
 
-        assert_eq!(options.delay, 7);
-        assert_eq!(options.strategy, ShardingStrategy::Autoshard);
-        assert_eq!(options.token, "aaa");
+        self
     }
 
-    #[test]
-    fn test_generics() {
-        fn decorator(foo: impl ToString) -> impl ToString {
-            foo
-        }
-
-        let mut options = SharderOptions::new(decorator("a"));
-
-        assert_eq!(options.token, "a");
+    /// Sets the token which stops the boot loop once cancelled.
+    ///
+    /// Refer to [`cancellation_token`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Share one token between two sharders:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::{CancellationToken, SharderOptions};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let cancellation = CancellationToken::new();
+    ///
+    /// let mut first = SharderOptions::new(&token);
+    /// first.cancellation_token(cancellation.clone());
+    ///
+    /// let mut second = SharderOptions::new(&token);
+    /// second.cancellation_token(cancellation);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`cancellation_token`]: #structfield.cancellation_token
+    pub fn cancellation_token(
+        &mut self,
+        token: CancellationToken,
+    ) -> &mut Self {
+        self.cancellation_token = token;
 
-        options.token(decorator("b"));
-        assert_eq!(options.token, "b");
+        self
     }
 
-    #[test]
-    fn test_minimum_delay() {
-        let mut options = SharderOptions::new("0");
-        options.delay(6);
-        assert_eq!(options.delay, 6);
-
-        // Assert that 4 becomes 5
-        options.delay(4);
-        assert_eq!(options.delay, 5);
+    /// Sets the maximum number of booted shards that may be waiting to be
+    /// received by the spawner.
+    ///
+    /// Refer to [`channel_capacity`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Boot at most 2 shards ahead of the consumer:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::SharderOptions;
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options.channel_capacity(2);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`channel_capacity`]: #structfield.channel_capacity
+    pub fn channel_capacity(&mut self, capacity: u64) -> &mut Self {
+        self.channel_capacity = Some(capacity);
+
+        self
+    }
+
+    /// Sets the hook to wait on before each identify.
+    ///
+    /// Refer to [`coordinator`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Refer to the [`Coordinator` examples].
+    ///
+    /// [`Coordinator` examples]: trait.Coordinator.html#examples
+    /// [`coordinator`]: #structfield.coordinator
+    pub fn coordinator(&mut self, coordinator: Arc<Coordinator>) -> &mut Self {
+        self.coordinator = Some(coordinator);
+
+        self
+    }
+
+    /// Sets the delay between shard starts, in seconds.
+    ///
+    /// This installs a [`delay_fn`] returning the delay, replacing any other.
+    /// Refer to [`delay`] for more information, and to [`delay_duration`] for
+    /// a delay with sub-second precision.
+    ///
+    /// # Examples
+    ///
+    /// Set a delay of 10 seconds:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::SharderOptions;
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options.delay(10);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Logs
+    ///
+    /// [WARN] When the sharding delay was below the minimum and was
+    /// automatically increased.
+    ///
+    /// [`delay`]: #structfield.delay
+    /// [`delay_duration`]: #method.delay_duration
+    /// [`delay_fn`]: #structfield.delay_fn
+    pub fn delay(&mut self, delay: u64) -> &mut Self {
+        self.delay_duration(Duration::from_secs(delay))
+    }
+
+    /// Sets the delay between shard starts.
+    ///
+    /// This is like [`delay`], except that the delay isn't limited to whole
+    /// seconds. Note that it's still increased to the [`min_delay`], which
+    /// must be lowered to 0 for sub-second delays.
+    ///
+    /// # Examples
+    ///
+    /// Set a delay of 250 milliseconds, such as when testing against a mock
+    /// gateway:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::SharderOptions;
+    /// use std::{env, time::Duration};
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options.min_delay(0).delay_duration(Duration::from_millis(250));
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Logs
+    ///
+    /// [WARN] When the sharding delay was below the minimum and was
+    /// automatically increased.
+    ///
+    /// [`delay`]: #method.delay
+    /// [`min_delay`]: #structfield.min_delay
+    pub fn delay_duration(&mut self, delay: Duration) -> &mut Self {
+        let minimum = Duration::from_secs(self.min_delay);
+
+        self.delay = if delay >= minimum {
+            delay
+        } else {
+            warn!(
+                "Increased sharding delay from {:?} to {:?}",
+                delay,
+                minimum
+            );
+
+            minimum
+        };
+
+        let duration = self.delay;
+        self.delay_fn = Some(Arc::new(move |_| duration));
+
+        self
+    }
+
+    /// Sets a function given the ID of a booted shard, returning the delay to
+    /// wait before booting the next shard.
+    ///
+    /// Refer to [`delay_fn`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Boot the first 10 shards every 5 seconds, and the rest every 10:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::SharderOptions;
+    /// use std::{env, time::Duration};
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options.delay_fn(|shard_id| {
+    ///     Duration::from_secs(if shard_id < 10 { 5 } else { 10 })
+    /// });
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`delay_fn`]: #structfield.delay_fn
+    pub fn delay_fn(
+        &mut self,
+        delay_fn: impl Fn(u64) -> Duration + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.delay_fn = Some(Arc::new(delay_fn));
+
+        self
+    }
+
+    /// Sets the maximum random amount to add to or subtract from the delay
+    /// before each round of boots.
+    ///
+    /// Refer to [`delay_jitter`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Wait between 5 and 9 seconds between boots:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::SharderOptions;
+    /// use std::{env, time::Duration};
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options.delay(7).delay_jitter(Duration::from_secs(2));
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`delay_jitter`]: #structfield.delay_jitter
+    pub fn delay_jitter(&mut self, jitter: Duration) -> &mut Self {
+        self.delay_jitter = jitter;
+
+        self
+    }
+
+    /// Returns the given delay with the [`delay_jitter`] applied, keeping it
+    /// at or above the minimum.
+    ///
+    /// [`delay_jitter`]: #structfield.delay_jitter
+    pub(crate) fn jittered(&self, delay: Duration) -> Duration {
+        let jitter = self.delay_jitter.as_secs() * 1000
+            + u64::from(self.delay_jitter.subsec_millis());
+
+        if jitter == 0 || self.fast_boot {
+            return delay;
+        }
+
+        let offset = rand::thread_rng().gen_range(0, jitter * 2 + 1);
+        let delay = delay + Duration::from_millis(offset);
+        let jittered = delay
+            .checked_sub(Duration::from_millis(jitter))
+            .unwrap_or_default();
+
+        jittered.max(Duration::from_secs(self.min_delay))
+    }
+
+    /// Sets the sender to emit structured boot events to.
+    ///
+    /// Refer to [`event_sink`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Refer to the [`SharderEvent` examples].
+    ///
+    /// [`SharderEvent` examples]: enum.SharderEvent.html#examples
+    /// [`event_sink`]: #structfield.event_sink
+    pub fn event_sink(
+        &mut self,
+        event_sink: UnboundedSender<SharderEvent>,
+    ) -> &mut Self {
+        self.event_sink = Some(event_sink);
+
+        self
+    }
+
+    /// Emits the given event to the [`event_sink`], if any.
+    ///
+    /// [`event_sink`]: #structfield.event_sink
+    pub(crate) fn emit(&self, event: SharderEvent) {
+        if let Some(ref event_sink) = self.event_sink {
+            let _ = event_sink.unbounded_send(event);
+        }
+    }
+
+    /// Returns the delay to wait after booting the shard with the given ID,
+    /// increased to the minimum if needed.
+    ///
+    /// # Logs
+    ///
+    /// [WARN] When the delay was below the minimum and was increased.
+    pub(crate) fn delay_after(&self, shard_id: u64) -> Duration {
+        if self.fast_boot {
+            return Duration::from_secs(0);
+        }
+
+        let minimum = Duration::from_secs(self.min_delay);
+        let delay = match self.delay_fn {
+            Some(ref delay_fn) => delay_fn(shard_id),
+            None => self.delay,
+        };
+
+        if delay >= minimum {
+            delay
+        } else {
+            warn!(
+                "Increased delay after shard {} from {:?} to {:?}",
+                shard_id,
+                delay,
+                minimum
+            );
+
+            minimum
+        }
+    }
+
+    /// Sets whether to boot shards without any delay between them.
+    ///
+    /// Refer to [`fast_boot`] for more information, and why this is
+    /// dangerous.
+    ///
+    /// # Examples
+    ///
+    /// Boot shards 0 to 3 of 4 without waiting between them:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::{SharderOptions, ShardingStrategy};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options
+    ///     .strategy(ShardingStrategy::Range(0, 3, 4))
+    ///     .fast_boot(true);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Logs
+    ///
+    /// [WARN] When enabling fast booting, as it will get you rate limited in
+    /// production.
+    ///
+    /// [`fast_boot`]: #structfield.fast_boot
+    pub fn fast_boot(&mut self, fast_boot: bool) -> &mut Self {
+        if fast_boot {
+            warn!(
+                "Fast booting shards without a delay; this will get you rate \
+                 limited in production"
+            );
+        }
+
+        self.fast_boot = fast_boot;
+
+        self
+    }
+
+    /// Sets the HTTP client to use when retrieving gateway information.
+    ///
+    /// Refer to [`http_client`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Reuse a client when autosharding:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity;
+    /// # extern crate serenity_sharder;
+    /// #
+    /// use serenity::http::Client;
+    /// use serenity_sharder::SharderOptions;
+    /// use std::sync::Arc;
+    ///
+    /// fn options(token: String, client: &Arc<Client>) -> SharderOptions {
+    ///     let mut options = SharderOptions::new(token);
+    ///     options.http_client(Arc::clone(client));
+    ///
+    ///     options
+    /// }
+    /// #
+    /// # fn main() {}
+    /// ```
+    ///
+    /// [`http_client`]: #structfield.http_client
+    pub fn http_client(&mut self, http_client: Arc<HttpClient>) -> &mut Self {
+        self.http_client = Some(http_client);
+
+        self
+    }
+
+    /// Sets the bucket to acquire a permit from before each identify.
+    ///
+    /// Refer to [`identify_bucket`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Refer to the [`IdentifyBucket` examples].
+    ///
+    /// [`IdentifyBucket` examples]: struct.IdentifyBucket.html#examples
+    /// [`identify_bucket`]: #structfield.identify_bucket
+    pub fn identify_bucket(
+        &mut self,
+        bucket: Arc<IdentifyBucket>,
+    ) -> &mut Self {
+        self.identify_bucket = Some(bucket);
+
+        self
+    }
+
+    /// Overrides the `[id, total]` shard array sent when identifying the shard
+    /// with the given ID.
+    ///
+    /// Refer to [`identify_overrides`] for more information, and why this is
+    /// dangerous.
+    ///
+    /// # Examples
+    ///
+    /// Identify shard 1 as if it were shard 0, to reproduce a session
+    /// conflict:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::{SharderOptions, ShardingStrategy};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options
+    ///     .strategy(ShardingStrategy::Range(0, 1, 2))
+    ///     .identify_override(1, [0, 2]);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Logs
+    ///
+    /// [WARN] That the shard array is being overridden.
+    ///
+    /// [`identify_overrides`]: #structfield.identify_overrides
+    #[cfg(feature = "dangerous_identify_override")]
+    pub fn identify_override(
+        &mut self,
+        shard_id: u64,
+        shard_info: [u64; 2],
+    ) -> &mut Self {
+        warn!(
+            "Overriding identify array of shard {} with {:?}",
+            shard_id,
+            shard_info,
+        );
+
+        self.identify_overrides.insert(shard_id, shard_info);
+
+        self
+    }
+
+    /// Returns the `[id, total]` shard array to identify the shard with.
+    pub(crate) fn shard_info(&self, shard_id: u64, total: u64) -> [u64; 2] {
+        #[cfg(feature = "dangerous_identify_override")]
+        {
+            if let Some(shard_info) = self.identify_overrides.get(&shard_id) {
+                return *shard_info;
+            }
+        }
+
+        [shard_id, total]
+    }
+
+    /// Sets the maximum number of shards that may identify at the same time,
+    /// overriding the value provided by Discord.
+    ///
+    /// Refer to [`max_concurrency`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Boot shards 0 to 63 of 64, up to 16 at a time:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::{SharderOptions, ShardingStrategy};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options
+    ///     .strategy(ShardingStrategy::Range(0, 63, 64))
+    ///     .max_concurrency(16);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`max_concurrency`]: #structfield.max_concurrency
+    pub fn max_concurrency(&mut self, max_concurrency: u64) -> &mut Self {
+        self.max_concurrency = Some(max_concurrency);
+
+        self
+    }
+
+    /// Sets the maximum number of shards to use when autosharding.
+    ///
+    /// Refer to [`max_shards`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Use the number of shards recommended by Discord, up to 32:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::SharderOptions;
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options.max_shards(32);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`max_shards`]: #structfield.max_shards
+    pub fn max_shards(&mut self, max_shards: u64) -> &mut Self {
+        self.max_shards = Some(max_shards);
+
+        self
+    }
+
+    /// Sets the callbacks to record metrics about booting shards with.
+    ///
+    /// Refer to [`metrics`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Refer to the [`SharderMetrics` examples].
+    ///
+    /// [`SharderMetrics` examples]: trait.SharderMetrics.html#examples
+    /// [`metrics`]: #structfield.metrics
+    pub fn metrics(&mut self, metrics: Arc<SharderMetrics>) -> &mut Self {
+        self.metrics = Some(metrics);
+
+        self
+    }
+
+    /// Sets the minimum number of seconds to wait between each round of boots.
+    ///
+    /// Refer to [`min_delay`] for more information. This should be set before
+    /// the [`delay`], as the delay is increased to the minimum when it's set.
+    ///
+    /// # Examples
+    ///
+    /// Boot every 2 seconds, with identifies rate limited by a shared bucket:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::{IdentifyBucket, SharderOptions};
+    /// use std::{env, sync::Arc};
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options
+    ///     .identify_bucket(Arc::new(IdentifyBucket::new()))
+    ///     .min_delay(2)
+    ///     .delay(2);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`delay`]: #method.delay
+    /// [`min_delay`]: #structfield.min_delay
+    pub fn min_delay(&mut self, min_delay: u64) -> &mut Self {
+        self.min_delay = min_delay;
+
+        self
+    }
+
+    /// Sets a function called with the ID of a shard right before it starts
+    /// booting.
+    ///
+    /// Refer to [`on_shard_start`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Print when shards start and stop:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::SharderOptions;
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options
+    ///     .on_shard_start(|shard_id| println!("Starting shard {}", shard_id))
+    ///     .on_shard_stop(|shard_id| println!("Stopped shard {}", shard_id));
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`on_shard_start`]: #structfield.on_shard_start
+    pub fn on_shard_start(
+        &mut self,
+        on_shard_start: impl Fn(u64) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.on_shard_start = Some(Arc::new(on_shard_start));
+
+        self
+    }
+
+    /// Sets a function called with the ID of a shard once the sharder has torn
+    /// it down.
+    ///
+    /// Refer to [`on_shard_stop`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Refer to the [examples of `on_shard_start`].
+    ///
+    /// [`on_shard_stop`]: #structfield.on_shard_stop
+    /// [examples of `on_shard_start`]: #method.on_shard_start
+    pub fn on_shard_stop(
+        &mut self,
+        on_shard_stop: impl Fn(u64) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.on_shard_stop = Some(Arc::new(on_shard_stop));
+
+        self
+    }
+
+    /// Calls the [`on_shard_start`] function, if any.
+    ///
+    /// [`on_shard_start`]: #structfield.on_shard_start
+    pub(crate) fn started(&self, shard_id: u64) {
+        if let Some(ref on_shard_start) = self.on_shard_start {
+            on_shard_start(shard_id);
+        }
+    }
+
+    /// Calls the [`on_shard_stop`] function, if any.
+    ///
+    /// [`on_shard_stop`]: #structfield.on_shard_stop
+    pub(crate) fn stopped(&self, shard_id: u64) {
+        if let Some(ref on_shard_stop) = self.on_shard_stop {
+            on_shard_stop(shard_id);
+        }
+    }
+
+    /// Sets the presence to set on every shard once it has booted.
+    ///
+    /// Refer to [`presence`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Show every shard as playing a game:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity;
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity::model::{gateway::Game, user::OnlineStatus};
+    /// use serenity_sharder::SharderOptions;
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options.presence(Some(Game::playing("~help")), OnlineStatus::Online);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`presence`]: #structfield.presence
+    pub fn presence(
+        &mut self,
+        game: Option<Game>,
+        status: OnlineStatus,
+    ) -> &mut Self {
+        self.presence = Some((game, status));
+
+        self
+    }
+
+    /// Sets the IDs of shards to boot before all others.
+    ///
+    /// Refer to [`priority_shards`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Boot shards 7 and 3 first, followed by the remaining shards 0 to 19:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::{SharderOptions, ShardingStrategy};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options
+    ///     .strategy(ShardingStrategy::Range(0, 19, 40))
+    ///     .priority_shards(vec![7, 3]);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`priority_shards`]: #structfield.priority_shards
+    pub fn priority_shards(&mut self, shard_ids: Vec<u64>) -> &mut Self {
+        self.priority_shards = shard_ids;
+
+        self
+    }
+
+    /// Orders the given shard IDs for booting according to the
+    /// [`boot_order`], moving the [`priority_shards`] to the front.
+    ///
+    /// [`boot_order`]: #structfield.boot_order
+    /// [`priority_shards`]: #structfield.priority_shards
+    pub(crate) fn ordered_shards(
+        &self,
+        shard_ids: impl IntoIterator<Item = u64>,
+    ) -> Vec<u64> {
+        let mut remaining = shard_ids.into_iter().collect::<Vec<_>>();
+
+        match self.boot_order {
+            Some(BootOrder::Ascending) => remaining.sort(),
+            Some(BootOrder::Descending) => remaining.sort_by(|a, b| b.cmp(a)),
+            Some(BootOrder::Custom(ref shard_ids)) => {
+                // Unlisted shards keep their order, as the sort is stable.
+                remaining.sort_by_key(|id| {
+                    shard_ids
+                        .iter()
+                        .position(|other| other == id)
+                        .unwrap_or(shard_ids.len())
+                });
+            },
+            Some(BootOrder::Nonexhaustive) => {
+                unreachable!("Don't specify this")
+            },
+            None => {},
+        }
+
+        let mut ordered = Vec::with_capacity(remaining.len());
+
+        for id in &self.priority_shards {
+            match remaining.iter().position(|other| other == id) {
+                Some(idx) => ordered.push(remaining.remove(idx)),
+                None => debug!("Ignoring priority shard {} not in strategy", id),
+            }
+        }
+
+        ordered.extend(remaining);
+
+        ordered
+    }
+
+    /// Sets the maximum duration to wait for a connected shard to be ready.
+    ///
+    /// Refer to [`ready_timeout`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Retry shards which aren't ready within 10 seconds up to 2 times:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::SharderOptions;
+    /// use std::{env, time::Duration};
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options.boot_retries(2).ready_timeout(Duration::from_secs(10));
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ready_timeout`]: #structfield.ready_timeout
+    pub fn ready_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.ready_timeout = Some(timeout);
+
+        self
+    }
+
+    /// Sets whether the [`ShardSpawner`] retains spawned shards.
+    ///
+    /// Refer to [`retain_shards`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Retain shards so they can be retrieved by ID:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::SharderOptions;
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options.retain_shards(true);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ShardSpawner`]: struct.ShardSpawner.html
+    /// [`retain_shards`]: #structfield.retain_shards
+    pub fn retain_shards(&mut self, retain_shards: bool) -> &mut Self {
+        self.retain_shards = retain_shards;
+
+        self
+    }
+
+    /// Sets a future which cancels the cancellation token once it resolves.
+    ///
+    /// Refer to [`shutdown_signal`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Stop booting shards once a oneshot channel is sent to:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use futures::{sync::oneshot, Future};
+    /// use serenity_sharder::SharderOptions;
+    /// use std::env;
+    ///
+    /// let (tx, rx) = oneshot::channel::<()>();
+    ///
+    /// let mut options = SharderOptions::new(env::var("DISCORD_TOKEN")?);
+    /// options.shutdown_signal(rx.map_err(|_| ()));
+    ///
+    /// // In a shutdown handler:
+    /// // tx.send(());
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`shutdown_signal`]: #structfield.shutdown_signal
+    pub fn shutdown_signal(
+        &mut self,
+        signal: impl Future<Item = (), Error = ()> + Send + 'static,
+    ) -> &mut Self {
+        let signal: ShutdownSignal = Box::new(signal);
+        self.shutdown_signal = Some(signal.shared());
+
+        self
+    }
+
+    /// Sets the sharding strategy to use for starting shards.
+    ///
+    /// Refer to [`strategy`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Set the sharding strategy to use a range of shards 0 to 19 of 40:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::{SharderOptions, ShardingStrategy};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options.strategy(ShardingStrategy::Range(0, 19, 40));
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`strategy`]: #structfield.strategy
+    pub fn strategy(&mut self, strategy: ShardingStrategy) -> &mut Self {
+        self.strategy = strategy;
+
+        self
+    }
+
+    /// Sets the bot's token.
+    ///
+    /// Refer to [`token`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Set the token to a new token after-the-fact:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::SharderOptions;
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    ///
+    /// // work here
+    /// # let use_alternative_token = true;
+    /// if use_alternative_token {
+    ///     options.token(env::var("ALTERNATE_TOKEN")?);
+    /// }
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`token`]: #structfield.token
+    #[inline]
+    pub fn token(&mut self, token: impl ToString) -> &mut Self {
+        self._token(token.to_string())
+    }
+
+    fn _token(&mut self, token: String) -> &mut Self {
+        self.token = token;
+
+        self
+    }
+
+    /// Sets the maximum duration to wait for all of the shards to boot.
+    ///
+    /// Refer to [`total_boot_timeout`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Fail if booting takes longer than 10 minutes:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::SharderOptions;
+    /// use std::{env, time::Duration};
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options.total_boot_timeout(Duration::from_secs(600));
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`total_boot_timeout`]: #structfield.total_boot_timeout
+    pub fn total_boot_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.total_boot_timeout = Some(timeout);
+
+        self
+    }
+
+    /// Checks that the [`token`] is accepted by Discord, by requesting the
+    /// gateway information of the bot.
+    ///
+    /// Calling this is optional. An invalid token otherwise isn't detected
+    /// until the boot loop runs, so this is useful to surface misconfiguration
+    /// before spawning. The [`http_client`] is used, if any.
+    ///
+    /// # Examples
+    ///
+    /// Validate the token before spawning:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity_sharder;
+    /// # extern crate tokio;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use futures::Future;
+    /// use serenity_sharder::SharderOptions;
+    /// use std::env;
+    /// use tokio::runtime::Runtime;
+    ///
+    /// let options = SharderOptions::new(env::var("DISCORD_TOKEN")?);
+    ///
+    /// let mut runtime = Runtime::new()?;
+    /// runtime.block_on(options.validate_token())?;
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Resolves to [`Error::Serenity`] when the request failed or was
+    /// unsuccessful, such as when the token is invalid.
+    ///
+    /// [`Error::Serenity`]: enum.Error.html#variant.Serenity
+    /// [`http_client`]: #structfield.http_client
+    /// [`token`]: #structfield.token
+    pub fn validate_token(
+        &self,
+    ) -> impl Future<Item = (), Error = Error> + Send {
+        gateway::bot(&self.token, self.http_client.clone()).map(|_| ())
+    }
+
+    /// Sets the base duration of the backoff between attempts to boot a shard,
+    /// taking and returning the options by value. Refer to
+    /// [`boot_backoff_base`] for more information.
+    ///
+    /// [`boot_backoff_base`]: #method.boot_backoff_base
+    pub fn with_boot_backoff_base(mut self, base: Duration) -> Self {
+        self.boot_backoff_base(base);
+
+        self
+    }
+
+    /// Sets the order to boot the shards of the strategy in, taking and
+    /// returning the options by value. Refer to [`boot_order`] for more
+    /// information.
+    ///
+    /// [`boot_order`]: #method.boot_order
+    pub fn with_boot_order(mut self, boot_order: BootOrder) -> Self {
+        self.boot_order(boot_order);
+
+        self
+    }
+
+    /// Sets the number of times to retry booting a shard that failed to boot,
+    /// taking and returning the options by value. Refer to [`boot_retries`] for
+    /// more information.
+    ///
+    /// [`boot_retries`]: #method.boot_retries
+    pub fn with_boot_retries(mut self, retries: u32) -> Self {
+        self.boot_retries(retries);
+
+        self
+    }
+
+    /// Sets the maximum duration to wait for a shard to boot, taking and
+    /// returning the options by value. Refer to [`boot_timeout`] for more
+    /// information.
+    ///
+    /// [`boot_timeout`]: #method.boot_timeout
+    pub fn with_boot_timeout(mut self, timeout: Duration) -> Self {
+        self.boot_timeout(timeout);
+
+        self
+    }
+
+    /// Sets the token which stops the boot loop once cancelled, taking and
+    /// returning the options by value. Refer to [`cancellation_token`] for more
+    /// information.
+    ///
+    /// [`cancellation_token`]: #method.cancellation_token
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token(token);
+
+        self
+    }
+
+    /// Sets the maximum number of booted shards that may be waiting to be
+    /// received by the spawner, taking and returning the options by value.
+    /// Refer to [`channel_capacity`] for more information.
+    ///
+    /// [`channel_capacity`]: #method.channel_capacity
+    pub fn with_channel_capacity(mut self, capacity: u64) -> Self {
+        self.channel_capacity(capacity);
+
+        self
+    }
+
+    /// Sets the hook to wait on before each identify, taking and returning the
+    /// options by value. Refer to [`coordinator`] for more information.
+    ///
+    /// [`coordinator`]: #method.coordinator
+    pub fn with_coordinator(mut self, coordinator: Arc<Coordinator>) -> Self {
+        self.coordinator(coordinator);
+
+        self
+    }
+
+    /// Sets the delay between shard starts, taking and returning the options by
+    /// value. This is useful for constructing options in a single expression.
+    /// Refer to [`delay`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Spawn shards with options constructed in a single expression:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use serenity_sharder::{self, SharderOptions, ShardingStrategy};
+    /// use std::env;
+    ///
+    /// let spawner = serenity_sharder::spawn(
+    ///     SharderOptions::new(env::var("DISCORD_TOKEN")?)
+    ///         .with_delay(10)
+    ///         .with_strategy(ShardingStrategy::auto()),
+    /// )?;
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`delay`]: #method.delay
+    pub fn with_delay(mut self, delay: u64) -> Self {
+        self.delay(delay);
+
+        self
+    }
+
+    /// Sets the delay between shard starts, taking and returning the options by
+    /// value. Refer to [`delay_duration`] for more information.
+    ///
+    /// [`delay_duration`]: #method.delay_duration
+    pub fn with_delay_duration(mut self, delay: Duration) -> Self {
+        self.delay_duration(delay);
+
+        self
+    }
+
+    /// Sets a function returning the delay to wait after booting a shard,
+    /// taking and returning the options by value. Refer to [`delay_fn`] for
+    /// more information.
+    ///
+    /// [`delay_fn`]: #method.delay_fn
+    pub fn with_delay_fn(
+        mut self,
+        delay_fn: impl Fn(u64) -> Duration + Send + Sync + 'static,
+    ) -> Self {
+        self.delay_fn(delay_fn);
+
+        self
+    }
+
+    /// Sets the maximum random amount to add to or subtract from the delay
+    /// before each round of boots, taking and returning the options by value.
+    /// Refer to [`delay_jitter`] for more information.
+    ///
+    /// [`delay_jitter`]: #method.delay_jitter
+    pub fn with_delay_jitter(mut self, jitter: Duration) -> Self {
+        self.delay_jitter(jitter);
+
+        self
+    }
+
+    /// Sets the sender to emit structured boot events to, taking and returning
+    /// the options by value. Refer to [`event_sink`] for more information.
+    ///
+    /// [`event_sink`]: #method.event_sink
+    pub fn with_event_sink(
+        mut self,
+        event_sink: UnboundedSender<SharderEvent>,
+    ) -> Self {
+        self.event_sink(event_sink);
+
+        self
+    }
+
+    /// Sets whether to boot shards without any delay between them, taking and
+    /// returning the options by value. Refer to [`fast_boot`] for more
+    /// information.
+    ///
+    /// [`fast_boot`]: #method.fast_boot
+    pub fn with_fast_boot(mut self, fast_boot: bool) -> Self {
+        self.fast_boot(fast_boot);
+
+        self
+    }
+
+    /// Sets the HTTP client to use when retrieving gateway information, taking
+    /// and returning the options by value. Refer to [`http_client`] for more
+    /// information.
+    ///
+    /// [`http_client`]: #method.http_client
+    pub fn with_http_client(mut self, http_client: Arc<HttpClient>) -> Self {
+        self.http_client(http_client);
+
+        self
+    }
+
+    /// Sets the bucket to acquire a permit from before each identify, taking
+    /// and returning the options by value. Refer to [`identify_bucket`] for
+    /// more information.
+    ///
+    /// [`identify_bucket`]: #method.identify_bucket
+    pub fn with_identify_bucket(mut self, bucket: Arc<IdentifyBucket>) -> Self {
+        self.identify_bucket(bucket);
+
+        self
+    }
+
+    /// Sets the maximum number of shards that may identify at the same time,
+    /// taking and returning the options by value. Refer to [`max_concurrency`]
+    /// for more information.
+    ///
+    /// [`max_concurrency`]: #method.max_concurrency
+    pub fn with_max_concurrency(mut self, max_concurrency: u64) -> Self {
+        self.max_concurrency(max_concurrency);
+
+        self
+    }
+
+    /// Sets the maximum number of shards to use when autosharding, taking and
+    /// returning the options by value. Refer to [`max_shards`] for more
+    /// information.
+    ///
+    /// [`max_shards`]: #method.max_shards
+    pub fn with_max_shards(mut self, max_shards: u64) -> Self {
+        self.max_shards(max_shards);
+
+        self
+    }
+
+    /// Sets the callbacks to record metrics about booting shards with, taking
+    /// and returning the options by value. Refer to [`metrics`] for more
+    /// information.
+    ///
+    /// [`metrics`]: #method.metrics
+    pub fn with_metrics(mut self, metrics: Arc<SharderMetrics>) -> Self {
+        self.metrics(metrics);
+
+        self
+    }
+
+    /// Sets the minimum number of seconds to wait between each round of
+    /// boots, taking and returning the options by value. Refer to
+    /// [`min_delay`] for more information.
+    ///
+    /// [`min_delay`]: #method.min_delay
+    pub fn with_min_delay(mut self, min_delay: u64) -> Self {
+        self.min_delay(min_delay);
+
+        self
+    }
+
+    /// Sets a function called with the ID of a shard right before it starts
+    /// booting, taking and returning the options by value. Refer to
+    /// [`on_shard_start`] for more information.
+    ///
+    /// [`on_shard_start`]: #method.on_shard_start
+    pub fn with_on_shard_start(
+        mut self,
+        on_shard_start: impl Fn(u64) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_shard_start(on_shard_start);
+
+        self
+    }
+
+    /// Sets a function called with the ID of a shard once the sharder has torn
+    /// it down, taking and returning the options by value. Refer to
+    /// [`on_shard_stop`] for more information.
+    ///
+    /// [`on_shard_stop`]: #method.on_shard_stop
+    pub fn with_on_shard_stop(
+        mut self,
+        on_shard_stop: impl Fn(u64) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_shard_stop(on_shard_stop);
+
+        self
+    }
+
+    /// Sets the presence to set on every shard once it has booted, taking and
+    /// returning the options by value. Refer to [`presence`] for more
+    /// information.
+    ///
+    /// [`presence`]: #method.presence
+    pub fn with_presence(
+        mut self,
+        game: Option<Game>,
+        status: OnlineStatus,
+    ) -> Self {
+        self.presence(game, status);
+
+        self
+    }
+
+    /// Sets the IDs of shards to boot before all others, taking and returning
+    /// the options by value. Refer to [`priority_shards`] for more information.
+    ///
+    /// [`priority_shards`]: #method.priority_shards
+    pub fn with_priority_shards(mut self, shard_ids: Vec<u64>) -> Self {
+        self.priority_shards(shard_ids);
+
+        self
+    }
+
+    /// Sets the maximum duration to wait for a connected shard to be ready,
+    /// taking and returning the options by value. Refer to [`ready_timeout`]
+    /// for more information.
+    ///
+    /// [`ready_timeout`]: #method.ready_timeout
+    pub fn with_ready_timeout(mut self, timeout: Duration) -> Self {
+        self.ready_timeout(timeout);
+
+        self
+    }
+
+    /// Sets whether the spawner retains spawned shards, taking and returning
+    /// the options by value. Refer to [`retain_shards`] for more information.
+    ///
+    /// [`retain_shards`]: #method.retain_shards
+    pub fn with_retain_shards(mut self, retain_shards: bool) -> Self {
+        self.retain_shards(retain_shards);
+
+        self
+    }
+
+    /// Sets a future which cancels the cancellation token once it resolves,
+    /// taking and returning the options by value. Refer to
+    /// [`shutdown_signal`] for more information.
+    ///
+    /// [`shutdown_signal`]: #method.shutdown_signal
+    pub fn with_shutdown_signal(
+        mut self,
+        signal: impl Future<Item = (), Error = ()> + Send + 'static,
+    ) -> Self {
+        self.shutdown_signal(signal);
+
+        self
+    }
+
+    /// Sets the sharding strategy to use for starting shards, taking and
+    /// returning the options by value. Refer to [`strategy`] for more
+    /// information.
+    ///
+    /// [`strategy`]: #method.strategy
+    pub fn with_strategy(mut self, strategy: ShardingStrategy) -> Self {
+        self.strategy(strategy);
+
+        self
+    }
+
+    /// Sets the bot's token, taking and returning the options by value. Refer
+    /// to [`token`] for more information.
+    ///
+    /// [`token`]: #method.token
+    pub fn with_token(mut self, token: impl ToString) -> Self {
+        self.token(token);
+
+        self
+    }
+
+    /// Sets the maximum duration to wait for all of the shards to boot, taking
+    /// and returning the options by value. Refer to [`total_boot_timeout`] for
+    /// more information.
+    ///
+    /// [`total_boot_timeout`]: #method.total_boot_timeout
+    pub fn with_total_boot_timeout(mut self, timeout: Duration) -> Self {
+        self.total_boot_timeout(timeout);
+
+        self
+    }
+}
+
+impl Debug for SharderOptions {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let mut debug = f.debug_struct("SharderOptions");
+
+        debug
+            .field("boot_backoff_base", &self.boot_backoff_base)
+            .field("boot_order", &self.boot_order)
+            .field("boot_retries", &self.boot_retries)
+            .field("boot_timeout", &self.boot_timeout)
+            .field("cancellation_token", &self.cancellation_token)
+            .field("channel_capacity", &self.channel_capacity)
+            .field(
+                "coordinator",
+                &self.coordinator.as_ref().map(|_| "Coordinator"),
+            )
+            .field("delay", &self.delay)
+            .field("delay_fn", &self.delay_fn.as_ref().map(|_| "Fn(u64)"))
+            .field("delay_jitter", &self.delay_jitter)
+            .field("event_sink", &self.event_sink)
+            .field("fast_boot", &self.fast_boot)
+            .field(
+                "http_client",
+                &self.http_client.as_ref().map(|_| "HttpClient"),
+            )
+            .field("identify_bucket", &self.identify_bucket);
+
+        #[cfg(feature = "dangerous_identify_override")]
+        debug.field("identify_overrides", &self.identify_overrides);
+
+        debug
+            .field("max_concurrency", &self.max_concurrency)
+            .field("max_shards", &self.max_shards)
+            .field("metrics", &self.metrics.as_ref().map(|_| "SharderMetrics"))
+            .field("min_delay", &self.min_delay)
+            .field(
+                "on_shard_start",
+                &self.on_shard_start.as_ref().map(|_| "Fn(u64)"),
+            )
+            .field(
+                "on_shard_stop",
+                &self.on_shard_stop.as_ref().map(|_| "Fn(u64)"),
+            )
+            .field("presence", &self.presence)
+            .field("priority_shards", &self.priority_shards)
+            .field("ready_timeout", &self.ready_timeout)
+            .field("retain_shards", &self.retain_shards)
+            .field(
+                "shutdown_signal",
+                &self.shutdown_signal.as_ref().map(|_| "Future"),
+            )
+            .field("strategy", &self.strategy)
+            .field("token", &self.token)
+            .field("total_boot_timeout", &self.total_boot_timeout)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{sync::mpsc, Future, Stream};
+    use std::{env, time::Duration};
+    use {BootOrder, Error, SharderEvent, SharderOptions, ShardingStrategy};
+
+    #[test]
+    fn test_boot_backoff() {
+        let mut options = SharderOptions::new("a");
+        options.boot_backoff_base(Duration::from_millis(100));
+
+        for attempt in 0..4 {
+            let min = Duration::from_millis(100 * 2u64.pow(attempt));
+            let backoff = options.boot_backoff(attempt);
+
+            assert!(backoff >= min);
+            assert!(backoff <= min + min / 2);
+        }
+    }
+
+    #[test]
+    fn test_fast_boot() {
+        let mut options = SharderOptions::new("a");
+        options
+            .delay_jitter(Duration::from_secs(2))
+            .fast_boot(true);
+
+        let delay = options.delay_after(0);
+        assert_eq!(delay, Duration::from_secs(0));
+        assert_eq!(options.jittered(delay), delay);
+    }
+
+    #[test]
+    fn test_delay_jitter() {
+        let mut options = SharderOptions::new("a");
+        let delay = Duration::from_secs(10);
+        assert_eq!(options.jittered(delay), delay);
+
+        options.delay_jitter(Duration::from_secs(2));
+
+        for _ in 0..10 {
+            let jittered = options.jittered(delay);
+
+            assert!(jittered >= Duration::from_secs(8));
+            assert!(jittered <= Duration::from_secs(12));
+        }
+
+        // Jittered delays aren't below the minimum.
+        for _ in 0..10 {
+            assert!(options.jittered(Duration::from_secs(5)) >= delay / 2);
+        }
+    }
+
+    #[test]
+    fn test_delay_fn() {
+        let mut options = SharderOptions::new("a");
+        assert_eq!(options.delay_after(0), Duration::from_secs(6));
+
+        options.delay(7);
+        assert_eq!(options.delay_after(3), Duration::from_secs(7));
+
+        // Delays below the minimum are increased to it.
+        options.delay_fn(|shard_id| Duration::from_secs(shard_id));
+        assert_eq!(options.delay_after(2), Duration::from_secs(5));
+        assert_eq!(options.delay_after(10), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_emit() {
+        let (tx, rx) = mpsc::unbounded();
+        let mut options = SharderOptions::new("a");
+        options.emit(SharderEvent::Completed {
+            booted: 0,
+            elapsed: Duration::from_secs(0),
+            retries: 0,
+        });
+        options.event_sink(tx);
+        options.emit(SharderEvent::ShardBooted {
+            id: 1,
+        });
+        drop(options);
+
+        let events = rx.collect().wait().unwrap();
+        assert_eq!(events, vec![SharderEvent::ShardBooted {
+            id: 1,
+        }]);
+    }
+
+    #[test]
+    fn test_by_value() {
+        let options = SharderOptions::new("a")
+            .with_delay(7)
+            .with_strategy(ShardingStrategy::Range(0, 4, 10))
+            .with_token("b");
+
+        assert_eq!(options.delay, Duration::from_secs(7));
+        assert_eq!(options.strategy, ShardingStrategy::Range(0, 4, 10));
+        assert_eq!(options.token, "b");
+    }
+
+    #[test]
+    fn test_fields() {
+        let mut options = SharderOptions::new("aaa");
+        options.delay(7).strategy(ShardingStrategy::Autoshard);
+
+        assert_eq!(options.delay, Duration::from_secs(7));
+        assert_eq!(options.ready_timeout, Some(Duration::from_secs(30)));
+        assert!(!options.retain_shards);
+        assert_eq!(options.strategy, ShardingStrategy::Autoshard);
+
+        options.retain_shards(true);
+        assert!(options.retain_shards);
+        assert_eq!(options.token, "aaa");
+    }
+
+    #[test]
+    fn test_from_env() {
+        let var = "SERENITY_SHARDER_TEST_TOKEN";
+
+        env::remove_var(var);
+        assert_eq!(
+            SharderOptions::from_env_var(var).unwrap_err(),
+            Error::MissingToken {
+                var: var.to_owned(),
+            }
+        );
+
+        env::set_var(var, "a");
+        assert_eq!(SharderOptions::from_env_var(var).unwrap().token, "a");
+    }
+
+    #[test]
+    fn test_generics() {
+        fn decorator(foo: impl ToString) -> impl ToString {
+            foo
+        }
+
+        let mut options = SharderOptions::new(decorator("a"));
+
+        assert_eq!(options.token, "a");
+
+        options.token(decorator("b"));
+        assert_eq!(options.token, "b");
+    }
+
+    #[test]
+    fn test_boot_order() {
+        let mut options = SharderOptions::new("a");
+        assert_eq!(options.ordered_shards(0..4), vec![0, 1, 2, 3]);
+        assert_eq!(options.ordered_shards(vec![2, 0, 1]), vec![2, 0, 1]);
+
+        options.boot_order(BootOrder::Descending);
+        assert_eq!(options.ordered_shards(0..4), vec![3, 2, 1, 0]);
+
+        options.boot_order(BootOrder::Ascending);
+        assert_eq!(options.ordered_shards(vec![2, 0, 1]), vec![0, 1, 2]);
+
+        options.boot_order(BootOrder::Custom(vec![3, 1]));
+        assert_eq!(options.ordered_shards(0..5), vec![3, 1, 0, 2, 4]);
+
+        options.boot_order(BootOrder::Ascending);
+
+        // Priority shards not in the strategy are ignored.
+        options.priority_shards(vec![2, 9, 0]);
+        assert_eq!(options.ordered_shards(0..4), vec![2, 0, 1, 3]);
+    }
+
+    #[cfg(feature = "dangerous_identify_override")]
+    #[test]
+    fn test_identify_override() {
+        let mut options = SharderOptions::new("a");
+        options.identify_override(1, [0, 2]);
+
+        assert_eq!(options.shard_info(0, 2), [0, 2]);
+        assert_eq!(options.shard_info(1, 2), [0, 2]);
+    }
+
+    #[test]
+    fn test_minimum_delay() {
+        let mut options = SharderOptions::new("0");
+        options.delay(6);
+        assert_eq!(options.delay, Duration::from_secs(6));
+
+        // Assert that 4 becomes 5
+        options.delay(4);
+        assert_eq!(options.delay, Duration::from_secs(5));
+
+        options.min_delay(2).delay(3);
+        assert_eq!(options.delay, Duration::from_secs(3));
+        assert_eq!(options.delay_after(0), Duration::from_secs(3));
+
+        options.delay(1);
+        assert_eq!(options.delay, Duration::from_secs(2));
+
+        options.delay_duration(Duration::from_millis(2500));
+        assert_eq!(options.delay, Duration::from_millis(2500));
+
+        options.delay_duration(Duration::from_millis(1500));
+        assert_eq!(options.delay, Duration::from_secs(2));
     }
 }
@@ -0,0 +1,30 @@
+/// The order to boot the shards of a sharding strategy in.
+///
+/// Set via [`SharderOptions::boot_order`]. Any
+/// [`SharderOptions::priority_shards`] are still booted first, and the rest
+/// are booted in this order.
+///
+/// [`SharderOptions::boot_order`]:
+/// struct.SharderOptions.html#structfield.boot_order
+/// [`SharderOptions::priority_shards`]:
+/// struct.SharderOptions.html#structfield.priority_shards
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum BootOrder {
+    /// Boot shards from the lowest ID to the highest.
+    Ascending,
+    /// Boot shards from the highest ID to the lowest.
+    ///
+    /// This is useful for phased rollouts, such as when the guilds on the
+    /// highest shards should come online first.
+    Descending,
+    /// Boot the shards with the given IDs first, in the given order, and then
+    /// the rest of the shards in the order of the strategy.
+    ///
+    /// Every ID must be one of the strategy's shards, otherwise resolving the
+    /// strategy fails with [`Error::InvalidStrategy`].
+    ///
+    /// [`Error::InvalidStrategy`]: enum.Error.html#variant.InvalidStrategy
+    Custom(Vec<u64>),
+    #[doc(hidden)]
+    Nonexhaustive,
+}
@@ -0,0 +1,418 @@
+use futures::{future, Future};
+use gateway::{self, SessionStartLimit};
+use serenity::http::Client as HttpClient;
+use std::sync::Arc;
+use {BootOrder, Error, SharderOptions, ShardingStrategy};
+
+/// The number of shards that Discord recommends for a bot, as returned by
+/// [`recommended_shards`].
+///
+/// [`recommended_shards`]: fn.recommended_shards.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecommendedShards {
+    /// The maximum number of shards that may identify at the same time.
+    ///
+    /// This is 1 if Discord didn't provide it.
+    pub max_concurrency: u64,
+    /// The number of shards Discord recommends the bot uses.
+    pub shards: u64,
+    __nonexhaustive: (),
+}
+
+/// The shards that would be booted with a set of options, as returned by
+/// [`plan`].
+///
+/// [`plan`]: fn.plan.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShardPlan {
+    /// The maximum number of shards that would identify at the same time.
+    pub max_concurrency: u64,
+    /// Discord's limit on how many times the bot may identify, if it was
+    /// retrieved when resolving [`ShardingStrategy::Autoshard`].
+    ///
+    /// This is useful for monitoring how close the bot is to exhausting its
+    /// session starts, such as when it's crash looping.
+    ///
+    /// [`ShardingStrategy::Autoshard`]:
+    /// enum.ShardingStrategy.html#variant.Autoshard
+    pub session_start_limit: Option<SessionStartLimit>,
+    /// The IDs of the shards that would be booted, in the order that they
+    /// would be booted.
+    pub shard_ids: Vec<u64>,
+    /// The resolved sharding strategy, with a defined set of shards.
+    pub strategy: ShardingStrategy,
+    /// The total number of shards utilized by the bot across all instances.
+    pub total: u64,
+    __nonexhaustive: (),
+}
+
+/// Resolves the shards that [`spawn`] would boot with the given options,
+/// without connecting any shards.
+///
+/// This is useful as a dry run, such as for checking the configuration of a
+/// deployment in CI. When the strategy is [`ShardingStrategy::Autoshard`],
+/// Discord is requested for the number of shards to use, as when spawning.
+///
+/// # Examples
+///
+/// Print the shards that would be booted:
+///
+/// ```rust,no_run
+/// # extern crate serenity_sharder;
+/// # extern crate tokio;
+/// #
+/// # use std::error::Error;
+/// #
+/// # fn main() -> Result<(), Box<Error>> {
+/// #
+/// use serenity_sharder::{self, SharderOptions, ShardingStrategy};
+/// use std::env;
+/// use tokio::runtime::Runtime;
+///
+/// let token = env::var("DISCORD_TOKEN")?;
+/// let options = SharderOptions::new(token)
+///     .with_strategy(ShardingStrategy::Range(0, 19, 40));
+///
+/// let mut runtime = Runtime::new()?;
+/// let plan = runtime.block_on(serenity_sharder::plan(&options))?;
+///
+/// println!("Booting shards {:?} of {}", plan.shard_ids, plan.total);
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Resolves to [`Error::InvalidStrategy`] when the values of the strategy are
+/// invalid. Refer to [`ShardingStrategy::validate`] for more information.
+///
+/// Resolves to [`Error::InvalidStrategy`] when a [`BootOrder::Custom`] order
+/// contains the ID of a shard that isn't in the resolved strategy.
+///
+/// Resolves to [`Error::InsufficientSessions`] when Discord reports fewer
+/// remaining session starts than the number of shards that would be booted.
+///
+/// Resolves to the error of resolving the strategy if it failed. Refer to
+/// [`ShardingStrategy::resolve`] for more information.
+///
+/// # Logs
+///
+/// [DEBUG] What the resolved sharding strategy is.
+/// [DEBUG] What the max concurrency used is.
+/// [DEBUG] How many session starts remain, if Discord was requested.
+/// [WARN] When Discord recommended more shards than the maximum.
+/// [WARN] When Discord recommended no shards, and 1 is used instead.
+/// [WARN] When the custom boot order contains a shard not in the strategy.
+///
+/// [`BootOrder::Custom`]: enum.BootOrder.html#variant.Custom
+/// [`Error::InsufficientSessions`]:
+/// enum.Error.html#variant.InsufficientSessions
+/// [`Error::InvalidStrategy`]: enum.Error.html#variant.InvalidStrategy
+/// [`ShardingStrategy::Autoshard`]:
+/// enum.ShardingStrategy.html#variant.Autoshard
+/// [`ShardingStrategy::resolve`]: enum.ShardingStrategy.html#method.resolve
+/// [`ShardingStrategy::validate`]:
+/// enum.ShardingStrategy.html#method.validate
+/// [`spawn`]: fn.spawn.html
+pub fn plan(
+    options: &SharderOptions,
+) -> impl Future<Item = ShardPlan, Error = Error> + Send {
+    let options = options.clone();
+
+    future::result(options.strategy.validate())
+        .and_then(move |_| resolve(&options))
+}
+
+/// Requests the number of shards that Discord recommends for the bot with the
+/// given token, along with the max concurrency, without booting any shards.
+///
+/// This is useful for provisioning infrastructure ahead of time, such as from
+/// an autoscaler, as it doesn't require [`SharderOptions`]. Refer to [`plan`]
+/// for the full set of shards that would be booted.
+///
+/// # Examples
+///
+/// Print the number of shards Discord recommends:
+///
+/// ```rust,no_run
+/// # extern crate serenity_sharder;
+/// # extern crate tokio;
+/// #
+/// # use std::error::Error;
+/// #
+/// # fn main() -> Result<(), Box<Error>> {
+/// #
+/// use serenity_sharder;
+/// use std::env;
+/// use tokio::runtime::Runtime;
+///
+/// let token = env::var("DISCORD_TOKEN")?;
+///
+/// let mut runtime = Runtime::new()?;
+/// let future = serenity_sharder::recommended_shards(&token);
+/// let recommended = runtime.block_on(future)?;
+///
+/// println!(
+///     "Discord recommends {} shards, identifying {} at a time",
+///     recommended.shards,
+///     recommended.max_concurrency
+/// );
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Resolves to [`Error::Serenity`] if the HTTP client could not be built, or
+/// if requesting the gateway information failed or was unsuccessful, such as
+/// when the token is invalid.
+///
+/// [`Error::Serenity`]: enum.Error.html#variant.Serenity
+/// [`SharderOptions`]: struct.SharderOptions.html
+/// [`plan`]: fn.plan.html
+pub fn recommended_shards(
+    token: &str,
+) -> impl Future<Item = RecommendedShards, Error = Error> + Send {
+    recommended_shards_with_client(token, None)
+}
+
+/// Requests the number of shards that Discord recommends for the bot with the
+/// given token, using the given HTTP client if any.
+///
+/// When a client is given, the request is made with the client's own token.
+/// Refer to [`recommended_shards`] for more information.
+///
+/// # Errors
+///
+/// Refer to [`recommended_shards`].
+///
+/// [`recommended_shards`]: fn.recommended_shards.html
+pub fn recommended_shards_with_client(
+    token: &str,
+    client: Option<Arc<HttpClient>>,
+) -> impl Future<Item = RecommendedShards, Error = Error> + Send {
+    gateway::bot(token, client).map(|gateway| RecommendedShards {
+        max_concurrency: gateway.max_concurrency.unwrap_or(1),
+        shards: gateway.shards,
+        __nonexhaustive: (),
+    })
+}
+
+/// Resolves the shards to boot with the given options, without validating the
+/// strategy.
+pub(crate) fn resolve(
+    options: &SharderOptions,
+) -> impl Future<Item = ShardPlan, Error = Error> + Send {
+    let options = options.clone();
+    let strategy = options
+        .strategy
+        .resolve_gateway(&options.token, options.http_client.clone());
+
+    strategy.and_then(move |(strategy, gateway)| {
+        let strategy = cap(&options, strategy);
+        debug!("Using resolved strategy of: {:?}", strategy);
+        let (shard_ids, total) = strategy
+            .shard_ids()
+            .expect("Resolved strategy has no shard IDs");
+        check_order(options.boot_order.as_ref(), &shard_ids, total)?;
+
+        let max_concurrency = options
+            .max_concurrency
+            .or_else(|| {
+                gateway.as_ref().and_then(|gateway| gateway.max_concurrency)
+            })
+            .unwrap_or(1);
+        debug!("Using max concurrency of {}", max_concurrency);
+
+        let session_start_limit = gateway
+            .map(|gateway| gateway.session_start_limit);
+        check_sessions(shard_ids.len() as u64, session_start_limit)?;
+
+        Ok(ShardPlan {
+            max_concurrency,
+            session_start_limit,
+            shard_ids: options.ordered_shards(shard_ids),
+            strategy,
+            total,
+            __nonexhaustive: (),
+        })
+    })
+}
+
+/// Checks that enough session starts remain to boot the given number of
+/// shards, if the limit is known.
+fn check_sessions(
+    needed: u64,
+    limit: Option<SessionStartLimit>,
+) -> Result<(), Error> {
+    let limit = match limit {
+        Some(limit) => limit,
+        None => return Ok(()),
+    };
+
+    debug!(
+        "{} of {} session starts remain",
+        limit.remaining,
+        limit.total
+    );
+
+    if limit.remaining < needed {
+        return Err(Error::InsufficientSessions {
+            needed,
+            remaining: limit.remaining,
+            reset_after: limit.reset_after,
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks that every shard in a custom boot order is one of the given shards of
+/// the resolved strategy.
+fn check_order(
+    order: Option<&BootOrder>,
+    shard_ids: &[u64],
+    total: u64,
+) -> Result<(), Error> {
+    let custom = match order {
+        Some(&BootOrder::Custom(ref custom)) => custom,
+        _ => return Ok(()),
+    };
+
+    match custom.iter().find(|id| !shard_ids.contains(id)) {
+        Some(id) => {
+            warn!("Boot order shard {} is not in the strategy", id);
+
+            Err(Error::InvalidStrategy {
+                end: shard_ids.iter().max().cloned().unwrap_or(0),
+                start: shard_ids.iter().min().cloned().unwrap_or(0),
+                total,
+            })
+        },
+        None => Ok(()),
+    }
+}
+
+/// Caps the total of a resolved autoshard strategy to the maximum number of
+/// shards, if any.
+fn cap(
+    options: &SharderOptions,
+    strategy: ShardingStrategy,
+) -> ShardingStrategy {
+    let max_shards = match (&options.strategy, options.max_shards) {
+        (&ShardingStrategy::Autoshard, Some(max_shards)) => max_shards.max(1),
+        _ => return strategy,
+    };
+
+    match strategy {
+        ShardingStrategy::Range(_, _, total) if total > max_shards => {
+            warn!(
+                "Discord recommended {} shards, using the maximum of {}",
+                total,
+                max_shards
+            );
+
+            ShardingStrategy::Range(0, max_shards - 1, max_shards)
+        },
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::Future;
+    use gateway::SessionStartLimit;
+    use queue::ShardQueue;
+    use std::time::Duration;
+    use super::{cap, check_sessions, plan};
+    use {BootOrder, Error, SharderOptions, ShardingStrategy};
+
+    #[test]
+    fn test_cap() {
+        let mut options = SharderOptions::new("a");
+        let recommended = ShardingStrategy::Range(0, 39, 40);
+        assert_eq!(cap(&options, recommended.clone()), recommended);
+
+        options.max_shards(32);
+        assert_eq!(
+            cap(&options, recommended),
+            ShardingStrategy::Range(0, 31, 32)
+        );
+
+        let fewer = ShardingStrategy::Range(0, 15, 16);
+        assert_eq!(cap(&options, fewer.clone()), fewer);
+
+        // Only autosharding is capped.
+        options.strategy(ShardingStrategy::Range(0, 39, 40));
+        let range = ShardingStrategy::Range(0, 39, 40);
+        assert_eq!(cap(&options, range.clone()), range);
+    }
+
+    #[test]
+    fn test_check_sessions() {
+        let reset_after = Duration::from_secs(60);
+        let limit = SessionStartLimit::new(8, reset_after, 1000);
+
+        assert!(check_sessions(16, None).is_ok());
+        assert!(check_sessions(8, Some(limit)).is_ok());
+        assert_eq!(
+            check_sessions(16, Some(limit)).unwrap_err(),
+            Error::InsufficientSessions {
+                needed: 16,
+                remaining: 8,
+                reset_after,
+            }
+        );
+    }
+
+    #[test]
+    fn test_plan() {
+        let options = SharderOptions::new("a")
+            .with_priority_shards(vec![3])
+            .with_strategy(ShardingStrategy::Range(1, 3, 10));
+        let plan = plan(&options).wait().unwrap();
+
+        assert_eq!(plan.max_concurrency, 1);
+        assert_eq!(plan.session_start_limit, None);
+        assert_eq!(plan.shard_ids, vec![3, 1, 2]);
+        assert_eq!(plan.strategy, ShardingStrategy::Range(1, 3, 10));
+        assert_eq!(plan.total, 10);
+    }
+
+    #[test]
+    fn test_plan_custom_order() {
+        let options = SharderOptions::new("a")
+            .with_boot_order(BootOrder::Custom(vec![5, 1, 3]))
+            .with_strategy(ShardingStrategy::Range(0, 5, 6));
+        let plan = plan(&options).wait().unwrap();
+        assert_eq!(plan.shard_ids, vec![5, 1, 3, 0, 2, 4]);
+
+        let queue = ShardQueue::default();
+        queue.extend(plan.shard_ids);
+        assert_eq!(queue.pop_round(1), vec![5]);
+        assert_eq!(queue.pop_round(1), vec![1]);
+        assert_eq!(queue.pop_round(1), vec![3]);
+        assert_eq!(queue.pop_round(1), vec![0]);
+
+        // A shard that isn't in the strategy can't be ordered.
+        let options = options.with_boot_order(BootOrder::Custom(vec![1, 6]));
+
+        match plan(&options).wait() {
+            Err(Error::InvalidStrategy { start: 0, end: 5, total: 6 }) => {},
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_plan_invalid() {
+        let options = SharderOptions::new("a")
+            .with_strategy(ShardingStrategy::Range(0, 10, 10));
+
+        match plan(&options).wait() {
+            Err(Error::InvalidStrategy { .. }) => {},
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+}
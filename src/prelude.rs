@@ -9,8 +9,10 @@
 //! ```
 
 pub use {
+    spawn,
     Error as SharderError,
     MessageProcessed,
+    ShardSpawner,
     SharderOptions,
     ShardingStrategy,
 };
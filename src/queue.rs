@@ -0,0 +1,205 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+use ShardingStrategy;
+
+#[derive(Debug, Default)]
+struct Inner {
+    guilds: HashMap<u64, usize>,
+    ids: VecDeque<u64>,
+    planned: u64,
+    sent: u64,
+    strategy: Option<ShardingStrategy>,
+}
+
+/// A queue of the IDs of shards that have yet to be booted, shared between the
+/// boot loop and the [`ShardSpawner`].
+///
+/// [`ShardSpawner`]: ../struct.ShardSpawner.html
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ShardQueue(Arc<Mutex<Inner>>);
+
+impl ShardQueue {
+    /// Appends the given IDs to the end of the queue.
+    pub fn extend(&self, ids: impl IntoIterator<Item = u64>) {
+        let mut inner = self.0.lock().expect("Shard queue poisoned");
+        let before = inner.ids.len();
+        inner.ids.extend(ids);
+        inner.planned += (inner.ids.len() - before) as u64;
+    }
+
+    /// Counts a shard as sent to the spawner.
+    pub fn sent(&self) {
+        self.0.lock().expect("Shard queue poisoned").sent += 1;
+    }
+
+    /// Returns the number of shards that have been sent to the spawner.
+    pub fn booted(&self) -> u64 {
+        self.0.lock().expect("Shard queue poisoned").sent
+    }
+
+    /// Counts the given number of shards as planned without queueing them,
+    /// such as shards that were booted elsewhere.
+    pub fn add_planned(&self, count: u64) {
+        self.0.lock().expect("Shard queue poisoned").planned += count;
+    }
+
+    /// Removes all pending IDs from the queue, so that they're no longer
+    /// planned to be booted.
+    pub fn clear(&self) {
+        let mut inner = self.0.lock().expect("Shard queue poisoned");
+        inner.planned -= inner.ids.len() as u64;
+        inner.ids.clear();
+    }
+
+    /// Returns a copy of the pending IDs, in the order they will be booted.
+    pub fn pending(&self) -> Vec<u64> {
+        let inner = self.0.lock().expect("Shard queue poisoned");
+
+        inner.ids.iter().cloned().collect()
+    }
+
+    /// Returns the number of shards planned to be booted: those that have been
+    /// added to the queue and not cleared from it.
+    pub fn planned(&self) -> u64 {
+        self.0.lock().expect("Shard queue poisoned").planned
+    }
+
+    /// Sets the resolved sharding strategy that the IDs were queued from.
+    pub fn set_strategy(&self, strategy: ShardingStrategy) {
+        self.0.lock().expect("Shard queue poisoned").strategy = Some(strategy);
+    }
+
+    /// Returns the resolved sharding strategy, if it has been resolved.
+    pub fn strategy(&self) -> Option<ShardingStrategy> {
+        self.0.lock().expect("Shard queue poisoned").strategy.clone()
+    }
+
+    /// Sets the number of guilds in the READY dispatch of the shard with the
+    /// given ID.
+    pub fn set_guilds(&self, shard_id: u64, guilds: usize) {
+        let mut inner = self.0.lock().expect("Shard queue poisoned");
+        inner.guilds.insert(shard_id, guilds);
+    }
+
+    /// Returns the number of guilds in the READY dispatch of the shard with
+    /// the given ID, if it has been booted.
+    pub fn guilds(&self, shard_id: u64) -> Option<usize> {
+        let inner = self.0.lock().expect("Shard queue poisoned");
+
+        inner.guilds.get(&shard_id).cloned()
+    }
+
+    /// Removes and returns the IDs of the next shards to boot at the same time,
+    /// up to one per identify concurrency bucket.
+    ///
+    /// This stops at the first ID in a bucket which has already been taken, to
+    /// preserve the boot order.
+    pub fn pop_round(&self, max_concurrency: u64) -> Vec<u64> {
+        let mut inner = self.0.lock().expect("Shard queue poisoned");
+        let queue = &mut inner.ids;
+        let mut buckets = HashSet::new();
+        let mut round = Vec::new();
+
+        while let Some(&id) = queue.front() {
+            let bucket = ShardingStrategy::bucket_for(id, max_concurrency);
+
+            if !buckets.insert(bucket) {
+                break;
+            }
+
+            queue.pop_front();
+            round.push(id);
+        }
+
+        round
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShardQueue;
+    use ShardingStrategy;
+
+    #[test]
+    fn test_order() {
+        let queue = ShardQueue::default();
+        queue.extend(vec![3, 1, 2]);
+        assert_eq!(queue.pending(), vec![3, 1, 2]);
+
+        assert_eq!(queue.pop_round(1), vec![3]);
+        assert_eq!(queue.pending(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_pop_round() {
+        let queue = ShardQueue::default();
+        queue.extend(vec![0, 1, 2, 5, 3, 4]);
+
+        assert_eq!(queue.pop_round(4), vec![0, 1, 2]);
+        assert_eq!(queue.pop_round(4), vec![5, 3, 4]);
+        assert!(queue.pop_round(4).is_empty());
+
+        queue.extend(0..3);
+        assert_eq!(queue.pop_round(1), vec![0]);
+    }
+
+    #[test]
+    fn test_booted() {
+        let queue = ShardQueue::default();
+        assert_eq!(queue.booted(), 0);
+
+        queue.sent();
+        queue.clone().sent();
+        assert_eq!(queue.booted(), 2);
+    }
+
+    #[test]
+    fn test_clear_is_shared() {
+        let queue = ShardQueue::default();
+        queue.extend(0..5);
+        let clone = queue.clone();
+        clone.clear();
+
+        assert!(queue.pending().is_empty());
+        assert!(queue.pop_round(1).is_empty());
+    }
+
+    #[test]
+    fn test_guilds() {
+        let queue = ShardQueue::default();
+        assert_eq!(queue.guilds(3), None);
+
+        queue.set_guilds(3, 1200);
+        assert_eq!(queue.clone().guilds(3), Some(1200));
+        assert_eq!(queue.guilds(4), None);
+    }
+
+    #[test]
+    fn test_planned() {
+        let queue = ShardQueue::default();
+        assert_eq!(queue.planned(), 0);
+
+        queue.extend(0..5);
+        queue.pop_round(1);
+        assert_eq!(queue.planned(), 5);
+
+        // Cleared IDs are no longer planned, but popped ones still are.
+        queue.clear();
+        assert_eq!(queue.planned(), 1);
+
+        queue.add_planned(2);
+        assert_eq!(queue.planned(), 3);
+        assert!(queue.pending().is_empty());
+    }
+
+    #[test]
+    fn test_strategy() {
+        let queue = ShardQueue::default();
+        assert!(queue.strategy().is_none());
+
+        queue.set_strategy(ShardingStrategy::Range(0, 9, 10));
+        assert_eq!(queue.strategy(), Some(ShardingStrategy::Range(0, 9, 10)));
+    }
+}
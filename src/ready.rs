@@ -0,0 +1,76 @@
+use futures::{
+    future::Shared,
+    sync::oneshot::Receiver,
+    Async,
+    Future,
+    Poll,
+};
+
+/// A future which resolves once every shard planned to be booted has been
+/// spawned.
+///
+/// This is returned by [`ShardSpawner::all_ready`].
+///
+/// If shards are cancelled via [`ShardSpawner::cancel_pending`], this resolves
+/// once the remaining planned shards have been spawned.
+///
+/// This resolves to an error if the boot loop stops before booting all of its
+/// shards, such as when it fails or is stopped by a [`CancellationToken`].
+///
+/// [`CancellationToken`]: struct.CancellationToken.html
+/// [`ShardSpawner::all_ready`]: struct.ShardSpawner.html#method.all_ready
+/// [`ShardSpawner::cancel_pending`]:
+/// struct.ShardSpawner.html#method.cancel_pending
+#[derive(Clone, Debug)]
+pub struct AllReady {
+    inner: Shared<Receiver<()>>,
+}
+
+impl AllReady {
+    pub(crate) fn new(inner: Shared<Receiver<()>>) -> Self {
+        Self {
+            inner,
+        }
+    }
+}
+
+impl Future for AllReady {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        match self.inner.poll() {
+            Ok(Async::Ready(_)) => Ok(Async::Ready(())),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Err(_) => Err(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{sync::oneshot, Future};
+    use super::AllReady;
+
+    #[test]
+    fn test_ready() {
+        let (tx, rx) = oneshot::channel();
+        let ready = AllReady::new(rx.shared());
+        let clone = ready.clone();
+
+        tx.send(()).unwrap();
+
+        assert!(ready.wait().is_ok());
+        assert!(clone.wait().is_ok());
+    }
+
+    #[test]
+    fn test_boot_stopped() {
+        let (tx, rx) = oneshot::channel::<()>();
+        let ready = AllReady::new(rx.shared());
+
+        drop(tx);
+
+        assert!(ready.wait().is_err());
+    }
+}
@@ -1,45 +1,108 @@
+use channel::{self, ShardSender};
 use futures::{
-    future::{self, Loop},
-    sync::mpsc::{self, UnboundedSender},
+    future::{self, Either, ExecuteErrorKind, Executor, Loop},
+    stream,
+    sync::oneshot::{self, Sender},
     Future,
+    Sink,
     Stream,
 };
-use serenity::gateway::Shard;
-use std::time::{Duration, Instant};
+use events;
+use plan;
+use queue::ShardQueue;
+use serenity::{
+    gateway::{GatewayError, Shard},
+    model::event::{Event, GatewayEvent},
+    Error as SerenityError,
+};
+use std::{
+    panic::AssertUnwindSafe,
+    time::{Duration, Instant},
+};
 use tokio::{
-    executor::{DefaultExecutor, Executor},
-    timer::Delay,
+    executor::{DefaultExecutor, SpawnError},
+    timer::{timeout::Error as TimeoutError, Delay, Timeout},
 };
-use {Error, ShardSpawner, SharderOptions};
+use {
+    AllReady,
+    BootFuture,
+    Error,
+    ShardSpawner,
+    SharderEvent,
+    SharderOptions,
+    ShardingStrategy,
+};
+
+/// Calls the `on_shard_stop` function of the options once dropped, unless the
+/// shard was sent to the spawner.
+///
+/// This is held while booting a shard, so that a shard which fails to boot,
+/// is dropped, or is abandoned by the boot loop stopping is reported as
+/// stopped exactly once.
+struct StopGuard {
+    options: SharderOptions,
+    sent: bool,
+    shard_id: u64,
+}
+
+impl Drop for StopGuard {
+    fn drop(&mut self) {
+        if !self.sent {
+            self.options.stopped(self.shard_id);
+        }
+    }
+}
 
 struct LoopState {
-    end: u64,
-    id: u64,
+    booted: u64,
+    max_concurrency: u64,
+    queue: ShardQueue,
+    ready: Option<Sender<()>>,
+    retries: u64,
+    started: Instant,
     total: u64,
-    tx: UnboundedSender<Shard>,
+    tx: Option<ShardSender>,
 }
 
 impl LoopState {
     fn new(
-        start: u64,
-        end: u64,
+        queue: ShardQueue,
+        max_concurrency: u64,
         total: u64,
-        tx: UnboundedSender<Shard>,
+        tx: ShardSender,
+        ready: Sender<()>,
     ) -> Self {
         Self {
-            id: start,
-            end,
+            booted: 0,
+            ready: Some(ready),
+            retries: 0,
+            started: Instant::now(),
+            max_concurrency,
+            queue,
             total,
-            tx,
+            tx: Some(tx),
         }
     }
 }
 
+/// A shard which has booted and is waiting to be sent to the spawner.
+struct Booted {
+    retries: u32,
+    shard: Shard,
+    shard_id: u64,
+    started: Instant,
+    stop: StopGuard,
+}
+
 /// Spawns a new [`ShardSpawner`], which is a stream of shards as they spawn
-/// and become "ready".
+/// and become ready.
+///
+/// A shard is only sent over the stream once its first READY or RESUMED
+/// dispatch has been received, so every shard yielded is fully connected.
 ///
 /// These are spawned in a queue according to the value of
-/// [`SharderOptions::delay`].
+/// [`SharderOptions::delay`], with up to [`SharderOptions::max_concurrency`]
+/// shards booting at the same time.
 ///
 /// # Examples
 ///
@@ -113,8 +176,8 @@ impl LoopState {
 ///                 .map(move |_| ())
 ///                 .map_err(SharderError::from))
 ///         }
-///     }).map_err(|_| ())
-/// });
+///     })
+/// }).map_err(|why| error!("Error running sharder: {:?}", why));
 ///
 /// // Finally, run the sharder on the tokio runtime.
 /// tokio::run(future);
@@ -125,63 +188,882 @@ impl LoopState {
 ///
 /// # Errors
 ///
-/// Returns [`Error::TokioExecutor`] when there is an issue spawning a future on
-/// the default executor.
+/// The strategy is resolved by the boot loop, so errors resolving it, such as
+/// an invalid token when autosharding, stop the boot loop. The same applies to
+/// errors booting a shard once [`SharderOptions::boot_retries`] have been
+/// exhausted, and to the [`SharderOptions::total_boot_timeout`] elapsing.
+/// These errors are logged and yielded by the [`ShardSpawner`] stream, which
+/// then ends.
+///
+/// Returns [`Error::InvalidStrategy`] when the values of the strategy are
+/// invalid. Refer to [`ShardingStrategy::validate`] for more information.
+///
+/// Returns [`Error::Unspawned`] when the default executor is not able to accept
+/// the boot future. The future is included in the error, so that it can be ran
+/// on another executor.
 ///
 /// # Logs
 ///
-/// [DEBUG] What the values of the sharding strategy are.
+/// [DEBUG] What the resolved sharding strategy is.
+/// [DEBUG] What the max concurrency used is.
 /// [DEBUG] When attempting to spawn a shard, indicating what the ID and total
 /// is.
-/// [DEBUG] The ID of the booted shard, and what the delay until the next spawn
-/// is.
+/// [DEBUG] When waiting for a permit from the identify bucket, if any.
+/// [DEBUG] When waiting for the coordinator, if any.
+/// [DEBUG] When a shard has received its READY or RESUMED dispatch.
+/// [DEBUG] The IDs of the booted shards, and what the delay until the next
+/// round of spawns is.
 /// [DEBUG] When finished sharding and the loop is breaking.
+/// [INFO] When all shards are ready, how long booting took, and how many boots
+/// were retried.
 /// [INFO] When the sharding strategy has been completed.
+/// [INFO] When the boot loop is stopped by the cancellation token.
+/// [INFO] When the shutdown signal resolves, cancelling the token.
+/// [WARN] When the executor is not able to accept the boot future.
+/// [WARN] When Discord recommended more shards than the maximum.
+/// [WARN] When Discord recommended no shards, and 1 is used instead.
+/// [WARN] When booting a shard timed out.
+/// [WARN] When booting all of the shards timed out.
+/// [WARN] When a shard wasn't ready within the ready timeout.
+/// [WARN] When booting a shard failed and is being retried.
+/// [WARN] When setting the presence of a booted shard failed.
+/// [ERROR] When booting a shard panicked.
+/// [ERROR] When the boot loop failed, and why.
 ///
-/// [`Error::TokioExecutor`]: enum.Error.html#variant.TokioExecutor
+/// [`Error::InvalidStrategy`]: enum.Error.html#variant.InvalidStrategy
+/// [`Error::Unspawned`]: enum.Error.html#variant.Unspawned
 /// [`ShardSpawner`]: struct.ShardSpawner.html
+/// [`SharderOptions::boot_retries`]:
+/// struct.SharderOptions.html#structfield.boot_retries
 /// [`SharderOptions::delay`]: struct.SharderOptions.html#structfield.delay
-pub fn spawn(
+/// [`SharderOptions::max_concurrency`]:
+/// struct.SharderOptions.html#structfield.max_concurrency
+/// [`SharderOptions::total_boot_timeout`]:
+/// struct.SharderOptions.html#structfield.total_boot_timeout
+/// [`ShardingStrategy::validate`]:
+/// enum.ShardingStrategy.html#method.validate
+pub fn spawn(options: SharderOptions) -> Result<ShardSpawner, Error> {
+    spawn_on(options, DefaultExecutor::current())
+}
+
+/// Spawns a new [`ShardSpawner`] like [`spawn`], except that the boot future
+/// is spawned on the given executor instead of the default executor.
+///
+/// This is useful when running on a specific runtime rather than within
+/// `tokio::run`, such as when embedding the sharder in a larger application.
+///
+/// # Examples
+///
+/// Spawn the boot future on a runtime's executor:
+///
+/// ```rust,no_run
+/// # extern crate serenity_sharder;
+/// # extern crate tokio;
+/// #
+/// # use std::error::Error;
+/// #
+/// # fn main() -> Result<(), Box<Error>> {
+/// #
+/// use serenity_sharder::{self, SharderOptions};
+/// use std::env;
+/// use tokio::runtime::Runtime;
+///
+/// let runtime = Runtime::new()?;
+///
+/// let options = SharderOptions::new(env::var("DISCORD_TOKEN")?);
+/// let spawner = serenity_sharder::spawn_on(options, runtime.executor())?;
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Returns [`Error::Unspawned`] when the executor is not able to accept the
+/// boot future. Refer to [`spawn`] for the other errors.
+///
+/// # Logs
+///
+/// Refer to [`spawn`].
+///
+/// [`Error::Unspawned`]: enum.Error.html#variant.Unspawned
+/// [`ShardSpawner`]: struct.ShardSpawner.html
+/// [`spawn`]: fn.spawn.html
+pub fn spawn_on(
+    options: SharderOptions,
+    executor: impl Executor<BootFuture>,
+) -> Result<ShardSpawner, Error> {
+    let (spawner, sharder, tx) = create(options)?;
+    let sharder = sharder.or_else(move |why| {
+        error!("Boot loop failed: {:?}", why);
+
+        // Send the error to the spawner so that its consumer can observe why
+        // sharding stopped, waiting for room if the channel is bounded.
+        tx.send(Err(why)).then(|_| Ok::<_, ()>(()))
+    });
+    let sharder: BootFuture = Box::new(sharder);
+
+    // The executor hands the future back if it can't accept it, so that it
+    // isn't lost.
+    if let Err(why) = executor.execute(sharder) {
+        warn!("Executor can't spawn the boot future: {:?}", why.kind());
+
+        let spawn_error = match why.kind() {
+            ExecuteErrorKind::Shutdown => SpawnError::shutdown(),
+            _ => SpawnError::at_capacity(),
+        };
+
+        return Err(Error::Unspawned(spawn_error, why.into_future()));
+    }
+
+    Ok(spawner)
+}
+
+/// Creates a new [`ShardSpawner`] along with the boot future which sends shards
+/// to it, without spawning the boot future.
+///
+/// This is like [`spawn`], except that the caller is responsible for running
+/// the returned boot future, such as on their own runtime. The boot future
+/// resolves once all shards have been booted or the boot loop was stopped by
+/// a [`CancellationToken`], and resolves to the error if the boot loop failed.
+/// This allows awaiting the completion of booting and observing why it
+/// failed.
+///
+/// Unlike with [`spawn`], the error is not sent over the [`ShardSpawner`],
+/// which instead ends when the boot loop fails.
+///
+/// No shards are booted until the boot future is polled.
+///
+/// # Examples
+///
+/// Run the boot future alongside processing of the shards, printing why it
+/// failed if it does:
+///
+/// ```rust,no_run
+/// # extern crate futures;
+/// # extern crate serenity_sharder;
+/// # extern crate tokio;
+/// #
+/// # use std::error::Error;
+/// #
+/// # fn main() -> Result<(), Box<Error>> {
+/// #
+/// use futures::{Future, Stream};
+/// use serenity_sharder::{self, SharderOptions};
+/// use std::env;
+///
+/// let options = SharderOptions::new(env::var("DISCORD_TOKEN")?);
+/// let (spawner, boot) = serenity_sharder::spawn_with_handle(options)?;
+///
+/// let boot = boot.map_err(|why| eprintln!("Boot loop failed: {}", why));
+/// let shards = spawner.for_each(|_shard| Ok(())).map_err(|_| ());
+///
+/// tokio::run(boot.join(shards).map(|_| ()));
+/// #
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidStrategy`] when the values of the strategy are
+/// invalid. Refer to [`ShardingStrategy::validate`] for more information.
+///
+/// # Logs
+///
+/// Refer to [`spawn`], except that the failure of the boot loop isn't logged.
+///
+/// [`CancellationToken`]: struct.CancellationToken.html
+/// [`Error::InvalidStrategy`]: enum.Error.html#variant.InvalidStrategy
+/// [`ShardingStrategy::validate`]:
+/// enum.ShardingStrategy.html#method.validate
+/// [`ShardSpawner`]: struct.ShardSpawner.html
+/// [`spawn`]: fn.spawn.html
+pub fn spawn_with_handle(
+    options: SharderOptions,
+) -> Result<
+    (ShardSpawner, impl Future<Item = (), Error = Error> + Send),
+    Error,
+> {
+    let (spawner, sharder, _) = create(options)?;
+
+    Ok((spawner, sharder))
+}
+
+/// Creates a new [`ShardSpawner`], the boot future which sends shards to it,
+/// and a sender to it.
+///
+/// [`ShardSpawner`]: struct.ShardSpawner.html
+fn create(
+    options: SharderOptions,
+) -> Result<
+    (
+        ShardSpawner,
+        impl Future<Item = (), Error = Error> + Send,
+        ShardSender,
+    ),
+    Error,
+> {
+    options.strategy.validate()?;
+
+    let (tx, rx) = channel::channel(options.channel_capacity);
+    let (ready_tx, ready_rx) = oneshot::channel();
+    let queue = ShardQueue::default();
+
+    let sharder = boot_until_cancelled(
+        options.clone(),
+        queue.clone(),
+        tx.clone(),
+        ready_tx,
+    );
+
+    let ready = AllReady::new(ready_rx.shared());
+    let spawner = ShardSpawner::new(rx, queue, ready, options);
+
+    Ok((spawner, sharder, tx))
+}
+
+/// Boots the shards of the strategy until done or the boot loop is stopped
+/// by the cancellation token or shutdown signal.
+pub(crate) fn boot_until_cancelled(
+    options: SharderOptions,
+    queue: ShardQueue,
+    tx: ShardSender,
+    ready: Sender<()>,
+) -> impl Future<Item = (), Error = Error> + Send {
+    let cancelled = options.cancellation_token.cancelled();
+    let cancelled = match options.shutdown_signal {
+        Some(ref signal) => {
+            let token = options.cancellation_token.clone();
+            let signal = signal.clone().then(move |_| -> Result<(), ()> {
+                info!("Shutdown signal received, cancelling");
+                token.cancel();
+
+                Ok(())
+            });
+
+            Either::A(cancelled.select(signal).then(|_| Ok::<_, ()>(())))
+        },
+        None => Either::B(cancelled),
+    };
+    let sharder = boot(options.clone(), queue.clone(), tx, ready);
+    let sharder = match options.total_boot_timeout {
+        Some(timeout) => {
+            let sharder = Timeout::new(sharder, timeout).map_err(move |why| {
+                timeout_error(why, || {
+                    warn!("Booting timed out after {:?}", timeout);
+
+                    Error::TotalBootTimeout {
+                        booted: queue.booted(),
+                        total: queue.planned(),
+                    }
+                })
+            });
+
+            Either::A(sharder)
+        },
+        None => Either::B(sharder),
+    };
+
+    sharder.select2(cancelled).then(|res| match res {
+        Ok(Either::A(_)) => {
+            info!("Completed shard strategy");
+
+            Ok(())
+        },
+        Ok(Either::B(_)) => {
+            info!("Sharding cancelled, stopping boot loop");
+
+            Ok(())
+        },
+        Err(Either::A((why, _))) => Err(why),
+        Err(Either::B(_)) => unreachable!("Cancellation never fails"),
+    })
+}
+
+/// Resolves the sharding strategy, and then boots each round of shards in
+/// turn.
+fn boot(
     options: SharderOptions,
-) -> Result<impl Stream<Item = Shard, Error = ()>, Error> {
-    let values = options.strategy.values().unwrap_or((0, 1, 0));
-    debug!("Using strategy values of: {:?}", values);
-    let (start, end, total) = values;
-
-    let (tx, rx) = mpsc::unbounded();
-    let state = LoopState::new(start, end, total, tx);
-    let delay = options.delay;
-
-    let sharder = future::loop_fn(state, move |state| {
-        debug!("Attempting to boot shard {} of {}", state.id, state.end);
-
-        Shard::new(options.token.to_owned(), [state.id, state.total])
-            .from_err::<Error>()
-            .map(move |shard| {
-                state.tx.unbounded_send(shard).expect("Error sending shard");
-
-                state
-            }).and_then(move |state| {
-                let until = Instant::now() + Duration::from_secs(delay);
-                debug!("Booted shard {}, delaying until {:?}", state.id, until);
-
-                Delay::new(until).map(|_| state).from_err()
-            }).and_then(|mut state| {
-                if state.id == state.end {
-                    debug!("Finished sharding, breaking loop...");
-
-                    Ok(Loop::Break(state))
-                } else {
-                    state.id += 1;
-
-                    Ok(Loop::Continue(state))
+    queue: ShardQueue,
+    tx: ShardSender,
+    ready: Sender<()>,
+) -> impl Future<Item = (), Error = Error> + Send {
+    plan::resolve(&options).and_then(move |plan| {
+        queue.set_strategy(plan.strategy);
+        queue.extend(plan.shard_ids);
+        let state = LoopState::new(
+            queue,
+            plan.max_concurrency,
+            plan.total,
+            tx,
+            ready,
+        );
+
+        future::loop_fn(state, move |state| boot_round(&options, state))
+    }).map(|_| ())
+}
+
+/// Boots the next round of shards in the queue at the same time, if any,
+/// sends them to the spawner in turn, and then waits for the delay before the
+/// next round.
+///
+/// Sending a shard waits for room in the channel if it's bounded, so the next
+/// round isn't booted until the spawner has received enough shards.
+fn boot_round(
+    options: &SharderOptions,
+    mut state: LoopState,
+) -> impl Future<Item = Loop<LoopState, LoopState>, Error = Error> + Send {
+    let shard_ids = state.queue.pop_round(state.max_concurrency);
+
+    if shard_ids.is_empty() {
+        debug!("Finished sharding, breaking loop...");
+        let elapsed = state.started.elapsed();
+        info!(
+            "All {} shards ready after {:?}, with {} retried boots",
+            state.booted,
+            elapsed,
+            state.retries
+        );
+
+        if let Some(ready) = state.ready.take() {
+            let _ = ready.send(());
+        }
+
+        options.emit(SharderEvent::Completed {
+            booted: state.booted,
+            elapsed,
+            retries: state.retries,
+        });
+
+        return Either::A(future::ok(Loop::Break(state)));
+    }
+
+    let delay = shard_ids
+        .iter()
+        .map(|&shard_id| options.delay_after(shard_id))
+        .max()
+        .unwrap_or_default();
+    let delay = options.jittered(delay);
+    let boots = shard_ids
+        .iter()
+        .map(|&shard_id| boot_shard(options, shard_id, &state))
+        .collect::<Vec<_>>();
+    let options = options.clone();
+
+    let round = future::join_all(boots).and_then(move |booted| {
+        stream::iter_ok::<_, Error>(booted).fold(state, move |state, booted| {
+            send_shard(&options, state, booted)
+        })
+    }).and_then(move |state| {
+        if delay == Duration::from_secs(0) {
+            debug!("Booted shards {:?}, not delaying", shard_ids);
+
+            return Either::A(future::ok(state));
+        }
+
+        let until = Instant::now() + delay;
+        debug!("Booted shards {:?}, delaying until {:?}", shard_ids, until);
+
+        Either::B(Delay::new(until).map(|_| state).from_err())
+    }).map(|state| {
+        if state.tx.as_ref().map_or(true, ShardSender::is_closed) {
+            debug!("Receiver closed, breaking loop...");
+
+            Loop::Break(state)
+        } else {
+            Loop::Continue(state)
+        }
+    });
+
+    Either::B(round)
+}
+
+/// Sends a booted shard to the spawner, waiting for room in the channel if
+/// it's bounded.
+///
+/// The shard only counts as booted once it has been sent. If the spawner's
+/// receiver has been closed, the shard is instead dropped and reported as
+/// stopped.
+fn send_shard(
+    options: &SharderOptions,
+    mut state: LoopState,
+    booted: Booted,
+) -> impl Future<Item = LoopState, Error = Error> + Send {
+    let Booted {
+        retries,
+        shard,
+        shard_id,
+        started,
+        mut stop,
+    } = booted;
+    state.retries += u64::from(retries);
+
+    let tx = match state.tx.take() {
+        Some(tx) => tx,
+        None => {
+            debug!("Receiver closed, dropping shard {}", shard_id);
+
+            return Either::A(future::ok(state));
+        },
+    };
+
+    let options = options.clone();
+
+    Either::B(tx.send(Ok(shard)).then(move |res| -> Result<_, Error> {
+        match res {
+            Ok(tx) => {
+                state.queue.sent();
+                state.booted += 1;
+                state.tx = Some(tx);
+                stop.sent = true;
+
+                if let Some(ref metrics) = options.metrics {
+                    metrics.on_boot_end(shard_id, started.elapsed());
                 }
-            })
-    }).map(|_| {
-        info!("Completed shard strategy");
-    }).map_err(|_| ());
 
-    DefaultExecutor::current().spawn(Box::new(sharder))?;
+                options.emit(SharderEvent::ShardBooted {
+                    id: shard_id,
+                });
+            },
+            Err(_) => {
+                debug!("Receiver closed, dropping shard {}", shard_id);
+            },
+        }
+
+        Ok(state)
+    }))
+}
+
+/// Connects a shard, retrying with a backoff up to the configured number of
+/// times if it fails with a recoverable error.
+///
+/// Resolves to the shard, the number of guilds in its READY dispatch if any,
+/// and the number of times booting it was retried.
+fn connect(
+    options: &SharderOptions,
+    shard_id: u64,
+    shard_info: [u64; 2],
+    max_concurrency: u64,
+) -> impl Future<Item = (Shard, Option<usize>, u32), Error = Error> + Send {
+    let options = options.clone();
+
+    future::loop_fn(0, move |attempt| {
+        let options = options.clone();
+        let shard = attempt_connect(
+            &options,
+            shard_id,
+            shard_info,
+            max_concurrency,
+        );
+
+        shard.then(move |res| {
+            let why = match res {
+                Ok((shard, guilds)) => {
+                    let connected = (shard, guilds, attempt);
+
+                    return Either::A(future::ok(Loop::Break(connected)));
+                },
+                Err(why) => why,
+            };
+
+            let retrying =
+                attempt < options.boot_retries && why.is_recoverable();
+            let backoff = if retrying {
+                Some(options.boot_backoff(attempt))
+            } else {
+                None
+            };
+
+            emit_rate_limited(&options, shard_id, &why, backoff);
+
+            let backoff = match backoff {
+                Some(backoff) => backoff,
+                None => return Either::A(future::err(why)),
+            };
+
+            warn!(
+                "Error booting shard {} (attempt {} of {}), retrying in {:?}: \
+                 {:?}",
+                shard_id,
+                attempt + 1,
+                options.boot_retries + 1,
+                backoff,
+                why
+            );
+
+            let retry = Delay::new(Instant::now() + backoff)
+                .from_err()
+                .map(move |_| Loop::Continue(attempt + 1));
+
+            Either::B(retry)
+        })
+    })
+}
+
+/// Emits [`SharderEvent::RateLimited`] if booting the shard failed because
+/// identifies are being sent too quickly, so that other subsystems can back
+/// off too.
+///
+/// This is emitted whether or not booting the shard is retried, after the
+/// given backoff if any.
+///
+/// [`SharderEvent::RateLimited`]: enum.SharderEvent.html#variant.RateLimited
+fn emit_rate_limited(
+    options: &SharderOptions,
+    shard_id: u64,
+    why: &Error,
+    retry_after: Option<Duration>,
+) {
+    if let Error::GatewayClosed { code: 4008, .. } = *why {
+        options.emit(SharderEvent::RateLimited {
+            id: shard_id,
+            retry_after,
+        });
+    }
+}
+
+/// Makes a single attempt to connect a shard and wait for it to become ready,
+/// abandoning it once the ready timeout or boot timeout elapses, if any.
+///
+/// If an identify bucket is set, a permit is acquired from it first, and then
+/// from the coordinator, if any.
+///
+/// Resolves to the shard and the number of guilds in its READY dispatch, if
+/// any.
+fn attempt_connect(
+    options: &SharderOptions,
+    shard_id: u64,
+    shard_info: [u64; 2],
+    max_concurrency: u64,
+) -> impl Future<Item = (Shard, Option<usize>), Error = Error> + Send {
+    let token = options.token.to_owned();
+    let bucket = match options.identify_bucket {
+        Some(ref bucket) => {
+            let key = ShardingStrategy::bucket_for(shard_id, max_concurrency);
+            debug!("Shard {} waiting for identify bucket {}", shard_id, key);
+
+            Either::A(bucket.acquire(key))
+        },
+        None => Either::B(future::ok(())),
+    };
+    let coordinator = options.coordinator.clone();
+    let permit = bucket.and_then(move |_| match coordinator {
+        Some(coordinator) => {
+            debug!("Shard {} waiting for the coordinator", shard_id);
+
+            Either::A(coordinator.acquire(shard_id))
+        },
+        None => Either::B(future::ok(())),
+    });
+    let ready_timeout = options.ready_timeout;
+    let shard = permit
+        .and_then(move |_| new_shard(token, shard_id, shard_info))
+        .and_then(move |shard| {
+            let ready = await_ready(shard, shard_id);
+            let timeout = match ready_timeout {
+                Some(timeout) => timeout,
+                None => return Either::A(ready),
+            };
+
+            let ready = Timeout::new(ready, timeout).map_err(move |why| {
+                timeout_error(why, || {
+                    warn!(
+                        "Shard {} wasn't ready after {:?}",
+                        shard_id,
+                        timeout
+                    );
+
+                    Error::ReadyTimeout {
+                        shard_id,
+                    }
+                })
+            });
+
+            Either::B(ready)
+        });
 
-    Ok(ShardSpawner::new(rx))
+    let timeout = match options.boot_timeout {
+        Some(timeout) => timeout,
+        None => return Either::A(shard),
+    };
+
+    let shard = Timeout::new(shard, timeout).map_err(move |why| {
+        timeout_error(why, || {
+            warn!("Shard {} timed out after {:?}", shard_id, timeout);
+
+            Error::BootTimeout {
+                shard_id,
+            }
+        })
+    });
+
+    Either::B(shard)
+}
+
+/// Converts the error of a future with a timeout, using the given function
+/// for the error if the timeout elapsed.
+fn timeout_error(
+    why: TimeoutError<Error>,
+    elapsed: impl FnOnce() -> Error,
+) -> Error {
+    if why.is_elapsed() {
+        elapsed()
+    } else if why.is_timer() {
+        Error::from(why.into_timer().expect("Timeout has no timer error"))
+    } else {
+        why.into_inner().expect("Timeout has no inner error")
+    }
+}
+
+/// Creates a new shard, attaching its ID to any error.
+fn new_shard(
+    token: String,
+    shard_id: u64,
+    shard_info: [u64; 2],
+) -> impl Future<Item = Shard, Error = Error> + Send {
+    Shard::new(token, shard_info)
+        .map_err(move |why| boot_error(shard_id, why))
+}
+
+/// Processes the messages of a newly connected shard until its first READY
+/// or RESUMED dispatch, resolving to the shard once it's ready.
+///
+/// The number of guilds the shard is responsible for is resolved alongside
+/// it when it received a READY dispatch, which includes them.
+///
+/// The connection closing before then is an error.
+fn await_ready(
+    shard: Shard,
+    shard_id: u64,
+) -> impl Future<Item = (Shard, Option<usize>), Error = Error> + Send {
+    future::loop_fn(shard, move |shard| {
+        let event = events::next_event(shard)
+            .map_err(move |why| boot_error(shard_id, why));
+
+        event.and_then(move |(event, shard)| match event {
+            Some(GatewayEvent::Dispatch(_, Event::Ready(ref ready))) => {
+                let guilds = ready.ready.guilds.len();
+                debug!("Shard {} is ready with {} guilds", shard_id, guilds);
+
+                Ok(Loop::Break((shard, Some(guilds))))
+            },
+            Some(GatewayEvent::Dispatch(_, Event::Resumed(_))) => {
+                debug!("Shard {} is ready", shard_id);
+
+                Ok(Loop::Break((shard, None)))
+            },
+            Some(_) => Ok(Loop::Continue(shard)),
+            None => {
+                let closed = GatewayError::Closed(None);
+
+                Err(boot_error(shard_id, SerenityError::Gateway(closed)))
+            },
+        })
+    })
+}
+
+/// Attaches the ID of a shard to an error booting it.
+///
+/// The gateway closing the connection is surfaced as
+/// [`Error::GatewayClosed`] so that its close code can be inspected.
+///
+/// [`Error::GatewayClosed`]: enum.Error.html#variant.GatewayClosed
+fn boot_error(shard_id: u64, source: SerenityError) -> Error {
+    match source {
+        SerenityError::Gateway(GatewayError::Closed(Some(ref frame))) => {
+            Error::GatewayClosed {
+                code: u16::from(frame.code),
+                reason: frame.reason.to_string(),
+            }
+        },
+        source => Error::ShardBoot {
+            shard_id,
+            source,
+        },
+    }
+}
+
+/// Boots a single shard, resolving to it once it's ready to be sent to the
+/// spawner.
+fn boot_shard(
+    options: &SharderOptions,
+    shard_id: u64,
+    state: &LoopState,
+) -> impl Future<Item = Booted, Error = Error> + Send {
+    debug!("Attempting to boot shard {} of {}", shard_id, state.total);
+    options.emit(SharderEvent::ShardBooting {
+        id: shard_id,
+        total: state.total,
+    });
+
+    if let Some(ref metrics) = options.metrics {
+        metrics.on_boot_start(shard_id);
+    }
+
+    options.started(shard_id);
+    let started = Instant::now();
+
+    let shard_info = options.shard_info(shard_id, state.total);
+    let presence = options.presence.clone();
+    let queue = state.queue.clone();
+    let stop = StopGuard {
+        options: options.clone(),
+        sent: false,
+        shard_id,
+    };
+
+    let boot = connect(options, shard_id, shard_info, state.max_concurrency)
+        .map(move |(mut shard, guilds, retries)| {
+            if let Some((game, status)) = presence {
+                if let Err(why) = shard.set_presence(game, status) {
+                    warn!(
+                        "Error setting shard {} presence: {:?}",
+                        shard_id,
+                        why
+                    );
+                }
+            }
+
+            // Recorded before sending, so that the count is available once
+            // the spawner receives the shard.
+            if let Some(guilds) = guilds {
+                queue.set_guilds(shard_id, guilds);
+            }
+
+            Booted {
+                retries,
+                shard,
+                shard_id,
+                started,
+                stop,
+            }
+        });
+
+    let options = options.clone();
+
+    // Catch panics so that they are logged and surfaced as an error instead
+    // of silently taking down the boot loop.
+    AssertUnwindSafe(boot).catch_unwind().then(move |res| {
+        let res = match res {
+            Ok(res) => res,
+            Err(_) => {
+                error!("Shard {} panicked while booting", shard_id);
+
+                Err(Error::ShardPanicked {
+                    shard_id,
+                })
+            },
+        };
+
+        // A booted shard is only reported once it has been sent.
+        if let Err(ref why) = res {
+            if let Some(ref metrics) = options.metrics {
+                metrics.on_boot_error(shard_id);
+            }
+
+            options.emit(SharderEvent::BootFailed {
+                error: why.to_string(),
+                id: shard_id,
+            });
+        }
+
+        res
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{
+        future::{ExecuteError, ExecuteErrorKind, Executor},
+        sync::mpsc,
+        Future,
+        Stream,
+    };
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+    use super::{emit_rate_limited, spawn_on, StopGuard};
+    use {BootFuture, Error, SharderEvent, SharderOptions};
+
+    /// An executor which has shut down, refusing every future.
+    struct ShutDown;
+
+    impl Executor<BootFuture> for ShutDown {
+        fn execute(
+            &self,
+            future: BootFuture,
+        ) -> Result<(), ExecuteError<BootFuture>> {
+            Err(ExecuteError::new(ExecuteErrorKind::Shutdown, future))
+        }
+    }
+
+    #[test]
+    fn test_emit_rate_limited() {
+        let (tx, rx) = mpsc::unbounded();
+        let options = SharderOptions::new("a").with_event_sink(tx);
+        let rate_limited = Error::GatewayClosed {
+            code: 4008,
+            reason: "Rate limited.".to_owned(),
+        };
+        let invalid_shard = Error::GatewayClosed {
+            code: 4010,
+            reason: "Invalid shard.".to_owned(),
+        };
+        let backoff = Duration::from_secs(2);
+
+        emit_rate_limited(&options, 1, &rate_limited, Some(backoff));
+        emit_rate_limited(&options, 2, &invalid_shard, Some(backoff));
+        // Out of retries, the rate limit is still surfaced.
+        emit_rate_limited(&options, 3, &rate_limited, None);
+        drop(options);
+
+        let events = rx.collect().wait().unwrap();
+        assert_eq!(events, vec![
+            SharderEvent::RateLimited {
+                id: 1,
+                retry_after: Some(backoff),
+            },
+            SharderEvent::RateLimited {
+                id: 3,
+                retry_after: None,
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_spawn_on_returns_future() {
+        match spawn_on(SharderOptions::new("a"), ShutDown) {
+            Err(Error::Unspawned(ref why, _)) => assert!(why.is_shutdown()),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_stop_guard() {
+        let stopped = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&stopped);
+        let options = SharderOptions::new("a").with_on_shard_stop(move |id| {
+            counter.fetch_add(id as usize, Ordering::SeqCst);
+        });
+
+        drop(StopGuard {
+            options: options.clone(),
+            sent: false,
+            shard_id: 3,
+        });
+        assert_eq!(stopped.load(Ordering::SeqCst), 3);
+
+        // Shards sent to the spawner aren't stopped.
+        drop(StopGuard {
+            options,
+            sent: true,
+            shard_id: 4,
+        });
+        assert_eq!(stopped.load(Ordering::SeqCst), 3);
+    }
 }
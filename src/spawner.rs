@@ -1,10 +1,40 @@
+use channel::{self, ShardReceiver};
 use futures::{
-    sync::mpsc::UnboundedReceiver,
+    future::{self, Either},
+    sync::{mpsc, oneshot},
+    Async,
+    Future,
     Poll,
     Stream,
 };
+use queue::ShardQueue;
 use serenity::gateway::Shard;
-use std::fmt::{Debug, Formatter, Result as FmtResult};
+use spawn;
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    iter,
+    time::Duration,
+};
+use {
+    AllReady,
+    BootOrder,
+    CancellationToken,
+    Error,
+    ShardEvents,
+    SharderOptions,
+    ShardingStrategy,
+    TakeUntilTimeout,
+};
+
+/// The closing of the shards held by a spawner whose boot loop timed out.
+type Closing = Box<Future<Item = (), Error = Error> + Send>;
+
+/// The receiver and queue of a boot loop started by resharding.
+struct Reshard {
+    inner: ShardReceiver,
+    queue: ShardQueue,
+}
 
 /// The shard spawner is a stream of instantiated shards.
 ///
@@ -14,47 +44,1599 @@ use std::fmt::{Debug, Formatter, Result as FmtResult};
 /// Once all shards have been spawned according to the [`ShardingStrategy`]
 /// requested, the stream will permanently end.
 ///
+/// If the boot loop fails, such as when the strategy can't be resolved, the
+/// stream yields the error and then ends.
+///
+/// If [`SharderOptions::retain_shards`] is enabled, shards are instead retained
+/// by the spawner as it's polled and the stream yields nothing, ending once
+/// all shards have been spawned.
+///
+/// # Thread safety
+///
+/// The spawner is `Send` and `'static`, so it can be moved into a spawned
+/// task or to another thread, such as by passing a future processing its
+/// shards to `tokio::spawn`.
+///
+/// It isn't `Sync`, as the shards it holds and the future closing them after
+/// a [total boot timeout] aren't `Sync`. As it's polled through a mutable
+/// reference anyway, share it between threads behind a lock such as a
+/// `Mutex`, which is `Sync`.
+///
 /// # Examples
 ///
 /// Refer to the [`spawn` examples] for more information.
 ///
-/// [`ShardingStrategy`]:
+/// [`ShardingStrategy`]: enum.ShardingStrategy.html
 /// [`spawn` examples]: fn.spawn.html#examples
+/// [`SharderOptions::retain_shards`]:
+/// struct.SharderOptions.html#structfield.retain_shards
 /// [`spawn`]: fn.spawn.html
+/// [total boot timeout]:
+/// struct.SharderOptions.html#structfield.total_boot_timeout
 pub struct ShardSpawner {
-    inner: UnboundedReceiver<Shard>,
+    closing: Option<(Closing, Error)>,
+    inner: ShardReceiver,
+    options: SharderOptions,
+    queue: ShardQueue,
+    ready: AllReady,
+    reshards: Vec<Reshard>,
+    retained: Option<HashMap<u64, Shard>>,
+    spawned: u64,
     __nonexhaustive: (),
 }
 
 impl ShardSpawner {
-    pub(crate) fn new(inner: UnboundedReceiver<Shard>) -> Self {
+    pub(crate) fn new(
+        inner: impl Into<ShardReceiver>,
+        queue: ShardQueue,
+        ready: AllReady,
+        options: SharderOptions,
+    ) -> Self {
         Self {
             __nonexhaustive: (),
-            inner,
+            closing: None,
+            reshards: Vec::new(),
+            retained: if options.retain_shards {
+                Some(HashMap::new())
+            } else {
+                None
+            },
+            inner: inner.into(),
+            spawned: 0,
+            options,
+            queue,
+            ready,
+        }
+    }
+
+    /// Creates a spawner which yields the given shards and then ends, without
+    /// booting any shards.
+    ///
+    /// This is useful for testing consumers of a spawner deterministically,
+    /// without a live gateway. The spawner is already [ready], and its
+    /// [`total`] and [`spawned`] counts are of the given shards once yielded.
+    /// It uses default options with an empty token, so it can't boot any
+    /// further shards, such as by re-sharding.
+    ///
+    /// # Examples
+    ///
+    /// Count the shards a consumer receives:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity;
+    /// # extern crate serenity_sharder;
+    /// #
+    /// use futures::Stream;
+    /// use serenity::gateway::Shard;
+    /// use serenity_sharder::ShardSpawner;
+    ///
+    /// fn count(shards: Vec<Shard>) -> usize {
+    ///     ShardSpawner::from_shards(shards).wait().count()
+    /// }
+    /// #
+    /// # fn main() {}
+    /// ```
+    ///
+    /// [ready]: #method.all_ready
+    /// [`spawned`]: #method.spawned
+    /// [`total`]: #method.total
+    pub fn from_shards(shards: Vec<Shard>) -> Self {
+        let (tx, rx) = mpsc::unbounded();
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let queue = ShardQueue::default();
+        queue.add_planned(shards.len() as u64);
+
+        for shard in shards {
+            let _ = tx.unbounded_send(Ok(shard));
+        }
+
+        let _ = ready_tx.send(());
+        let ready = AllReady::new(ready_rx.shared());
+
+        Self::new(rx, queue, ready, SharderOptions::new(""))
+    }
+
+    /// Returns a future which resolves once every shard planned to be booted
+    /// has been spawned.
+    ///
+    /// This is the natural "bot is up" marker, such as for readiness probes.
+    /// Refer to [`AllReady`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Print when all shards are ready, while processing shards as they're
+    /// spawned:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity_sharder;
+    /// # extern crate tokio;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use futures::{Future, Stream};
+    /// use serenity_sharder::{self, SharderOptions};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let spawner = serenity_sharder::spawn(SharderOptions::new(token))?;
+    /// let ready = spawner.all_ready().map(|_| println!("All shards ready"));
+    ///
+    /// let shards = spawner.for_each(|_shard| Ok(())).map_err(|_| ());
+    ///
+    /// tokio::run(shards.join(ready).map(|_| ()));
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`AllReady`]: struct.AllReady.html
+    pub fn all_ready(&self) -> AllReady {
+        self.ready.clone()
+    }
+
+    /// Returns the token which stops the boot loop once cancelled.
+    ///
+    /// This is the token from [`SharderOptions::cancellation_token`]. Refer to
+    /// [`CancellationToken`] for more information.
+    ///
+    /// [`CancellationToken`]: struct.CancellationToken.html
+    /// [`SharderOptions::cancellation_token`]:
+    /// struct.SharderOptions.html#structfield.cancellation_token
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.options.cancellation_token.clone()
+    }
+
+    /// Cancels booting of all shards that have not yet started booting.
+    ///
+    /// Shards that have already been spawned, as well as the shard currently
+    /// being booted, are not affected. Once the current boot completes the
+    /// boot loop stops, and the stream ends after yielding the remaining
+    /// spawned shards.
+    ///
+    /// Unlike pausing, this is permanent: cancelled shards can not be resumed.
+    ///
+    /// # Examples
+    ///
+    /// Stop booting once 10 shards have been spawned:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity_sharder;
+    /// # extern crate tokio;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use futures::{future, Async, Future, Stream};
+    /// use serenity_sharder::{self, SharderOptions};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut spawner = serenity_sharder::spawn(SharderOptions::new(token))?;
+    /// let mut spawned = 0;
+    ///
+    /// let future = future::poll_fn(move || loop {
+    ///     match spawner.poll().map_err(|_| ())? {
+    ///         Async::Ready(Some(_shard)) => {
+    ///             spawned += 1;
+    ///
+    ///             if spawned == 10 {
+    ///                 spawner.cancel_pending();
+    ///             }
+    ///         },
+    ///         Async::Ready(None) => return Ok(Async::Ready(())),
+    ///         Async::NotReady => return Ok(Async::NotReady),
+    ///     }
+    /// });
+    ///
+    /// tokio::run(future);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Logs
+    ///
+    /// [INFO] The IDs of the shards that were cancelled.
+    pub fn cancel_pending(&self) {
+        info!("Cancelling pending shards: {:?}", self.pending_shard_ids());
+
+        for queue in self.queues() {
+            queue.clear();
+        }
+    }
+
+    /// Converts the spawner into a stream of shards paired with their IDs.
+    ///
+    /// The ID is taken from each shard's shard info, and so is correct for
+    /// every sharding strategy.
+    ///
+    /// # Examples
+    ///
+    /// Print the ID of each shard as it's spawned:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity_sharder;
+    /// # extern crate tokio;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use futures::{Future, Stream};
+    /// use serenity_sharder::{self, SharderOptions};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let spawner = serenity_sharder::spawn(SharderOptions::new(token))?;
+    ///
+    /// let future = spawner.enumerated().for_each(|(shard_id, _shard)| {
+    ///     println!("Spawned shard {}", shard_id);
+    ///
+    ///     Ok(())
+    /// }).map_err(|why| eprintln!("Error booting shards: {}", why));
+    ///
+    /// tokio::run(future);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn enumerated(self) -> impl Stream<Item = (u64, Shard), Error = Error> {
+        self.map(|shard| (shard.shard_info()[0], shard))
+    }
+
+    /// Converts the spawner into a stream of the events received by all of its
+    /// shards, paired with the ID of the shard which received them.
+    ///
+    /// This drives each shard as it's spawned, replacing the loop of receiving,
+    /// parsing, and processing messages shown in the [`spawn` examples].
+    /// Refer to [`ShardEvents`] for more information.
+    ///
+    /// Shards retained via [`SharderOptions::retain_shards`] aren't yielded by
+    /// the spawner, and so their events aren't included.
+    ///
+    /// # Examples
+    ///
+    /// Print the ID of every shard which receives a message:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity;
+    /// # extern crate serenity_sharder;
+    /// # extern crate tokio;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use futures::{Future, Stream};
+    /// use serenity::model::event::{Event, GatewayEvent};
+    /// use serenity_sharder::{self, SharderOptions};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let spawner = serenity_sharder::spawn(SharderOptions::new(token))?;
+    ///
+    /// let future = spawner.events().for_each(|(shard_id, event)| {
+    ///     if let GatewayEvent::Dispatch(_, Event::MessageCreate(_)) = event {
+    ///         println!("Shard {} received a message", shard_id);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }).map_err(|why| eprintln!("Error running shards: {}", why));
+    ///
+    /// tokio::run(future);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ShardEvents`]: struct.ShardEvents.html
+    /// [`SharderOptions::retain_shards`]:
+    /// struct.SharderOptions.html#structfield.retain_shards
+    /// [`spawn` examples]: fn.spawn.html#examples
+    pub fn events(self) -> ShardEvents {
+        ShardEvents::new(self)
+    }
+
+    /// Merges another spawner into this one, so that the returned spawner
+    /// yields the shards of both, in the order they're spawned.
+    ///
+    /// This is useful when booting is split across multiple calls to [`spawn`]
+    /// in the same process, such as with different strategies or staggered
+    /// starts, as the shards can then be processed as a single stream. The
+    /// returned spawner ends once the boot loops of both have ended, and
+    /// yields the error of either if it failed.
+    ///
+    /// The counts of the returned spawner, such as [`spawned`] and [`total`],
+    /// cover both. Its options are this spawner's, and so [`shutdown`] only
+    /// cancels this spawner's [`cancellation_token`], and readiness is only
+    /// reported for this spawner's strategy, as with [`reshard`].
+    ///
+    /// Shards retained by the other spawner are retained by the returned one
+    /// if this spawner retains shards, and are otherwise yielded.
+    ///
+    /// # Examples
+    ///
+    /// Boot two ranges of shards with separate options, processing them
+    /// together:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity_sharder;
+    /// # extern crate tokio;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use futures::{Future, Stream};
+    /// use serenity_sharder::{self, SharderOptions, ShardingStrategy};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let first = SharderOptions::new(token.clone())
+    ///     .with_strategy(ShardingStrategy::Range(0, 9, 20));
+    /// let second = SharderOptions::new(token)
+    ///     .with_strategy(ShardingStrategy::Range(10, 19, 20));
+    ///
+    /// let spawner = serenity_sharder::spawn(first)?
+    ///     .merge(serenity_sharder::spawn(second)?);
+    ///
+    /// let future = spawner.for_each(|shard| {
+    ///     println!("Spawned shard {}", shard.shard_info()[0]);
+    ///
+    ///     Ok(())
+    /// }).map_err(|why| eprintln!("Error booting shards: {}", why));
+    ///
+    /// tokio::run(future);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`cancellation_token`]: #method.cancellation_token
+    /// [`reshard`]: #method.reshard
+    /// [`shutdown`]: #method.shutdown
+    /// [`spawn`]: fn.spawn.html
+    /// [`spawned`]: #method.spawned
+    /// [`total`]: #method.total
+    pub fn merge(mut self, mut other: ShardSpawner) -> Self {
+        self.spawned += other.spawned;
+
+        if let Some(retained) = other.retained.take() {
+            match self.retained {
+                Some(ref mut own) => own.extend(retained),
+                None => {
+                    // Yield the shards instead, which counts them as spawned
+                    // again as they're received.
+                    let (tx, rx) = mpsc::unbounded();
+                    self.spawned -= retained.len() as u64;
+
+                    for (_, shard) in retained {
+                        let _ = tx.unbounded_send(Ok(shard));
+                    }
+
+                    self.reshards.push(Reshard {
+                        inner: rx.into(),
+                        queue: ShardQueue::default(),
+                    });
+                },
+            }
+        }
+
+        self.reshards.push(Reshard {
+            inner: other.inner,
+            queue: other.queue,
+        });
+        self.reshards.extend(other.reshards);
+
+        self
+    }
+
+    /// Returns the message broker partition to route the events of a shard to.
+    ///
+    /// This is useful for adapters which push events to a broker, so that the
+    /// events of each shard consistently land on the same partition. The
+    /// partition of a shard is `shard_id % partitions`, and this mapping is
+    /// stable across versions of this library.
+    ///
+    /// A `partitions` of 0 is treated as 1.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serenity_sharder::ShardSpawner;
+    ///
+    /// assert_eq!(ShardSpawner::partition_key(17, 8), 1);
+    /// ```
+    pub fn partition_key(shard_id: u64, partitions: u64) -> u64 {
+        shard_id % partitions.max(1)
+    }
+
+    /// Returns a reference to the retained shard with the given ID.
+    ///
+    /// Returns `None` if the shard has not been spawned yet, or if
+    /// [`SharderOptions::retain_shards`] is not enabled.
+    ///
+    /// # Examples
+    ///
+    /// Boot all shards and then retrieve shard 0:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity_sharder;
+    /// # extern crate tokio;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use futures::{future, Async, Stream};
+    /// use serenity_sharder::{self, SharderOptions};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options.retain_shards(true);
+    ///
+    /// let mut spawner = serenity_sharder::spawn(options)?;
+    ///
+    /// let future = future::poll_fn(move || loop {
+    ///     match spawner.poll().map_err(|_| ())? {
+    ///         Async::Ready(Some(_)) => {},
+    ///         Async::Ready(None) => {
+    ///             if let Some(shard) = spawner.get(0) {
+    ///                 println!("Shard 0: {:?}", shard.shard_info());
+    ///             }
+    ///
+    ///             return Ok(Async::Ready(()));
+    ///         },
+    ///         Async::NotReady => return Ok(Async::NotReady),
+    ///     }
+    /// });
+    ///
+    /// tokio::run(future);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`SharderOptions::retain_shards`]:
+    /// struct.SharderOptions.html#structfield.retain_shards
+    pub fn get(&self, shard_id: u64) -> Option<&Shard> {
+        self.retained.as_ref()?.get(&shard_id)
+    }
+
+    /// Returns a mutable reference to the retained shard with the given ID.
+    ///
+    /// This is useful for sending a presence update to a shard after booting.
+    /// Refer to [`get`] for more information.
+    ///
+    /// [`get`]: #method.get
+    pub fn get_mut(&mut self, shard_id: u64) -> Option<&mut Shard> {
+        self.retained.as_mut()?.get_mut(&shard_id)
+    }
+
+    /// Returns an iterator over the retained shards, in no particular order.
+    ///
+    /// This is empty if [`SharderOptions::retain_shards`] is not enabled.
+    ///
+    /// [`SharderOptions::retain_shards`]:
+    /// struct.SharderOptions.html#structfield.retain_shards
+    pub fn shards(&self) -> impl Iterator<Item = &Shard> {
+        self.retained.iter().flat_map(|retained| retained.values())
+    }
+
+    /// Returns the latency of the last heartbeat of each retained shard, keyed
+    /// by shard ID.
+    ///
+    /// The latency is `None` for shards which have not yet completed a
+    /// heartbeat round-trip.
+    ///
+    /// This is empty if [`SharderOptions::retain_shards`] is not enabled.
+    ///
+    /// [`SharderOptions::retain_shards`]:
+    /// struct.SharderOptions.html#structfield.retain_shards
+    pub fn latencies(&self) -> HashMap<u64, Option<Duration>> {
+        self.retained
+            .iter()
+            .flat_map(|retained| retained.iter())
+            .map(|(&shard_id, shard)| (shard_id, shard.latency()))
+            .collect()
+    }
+
+    /// Returns the number of guilds each retained shard is responsible for,
+    /// keyed by shard ID.
+    ///
+    /// This is the number of guilds in the READY dispatch the shard received
+    /// while booting, and so doesn't reflect guilds joined or left since.
+    /// It's useful for checking that guilds are balanced across shards, and
+    /// for deciding when to [`reshard`]. Shards which resumed rather than
+    /// receiving a READY dispatch aren't included.
+    ///
+    /// This is empty if [`SharderOptions::retain_shards`] is not enabled.
+    ///
+    /// # Examples
+    ///
+    /// Print the number of guilds of the busiest shard:
+    ///
+    /// ```rust,no_run
+    /// # extern crate serenity_sharder;
+    /// #
+    /// use serenity_sharder::ShardSpawner;
+    ///
+    /// fn print_busiest(spawner: &ShardSpawner) {
+    ///     let busiest = spawner
+    ///         .guild_counts()
+    ///         .into_iter()
+    ///         .max_by_key(|&(_, guilds)| guilds);
+    ///
+    ///     if let Some((shard_id, guilds)) = busiest {
+    ///         println!("Shard {} has {} guilds", shard_id, guilds);
+    ///     }
+    /// }
+    /// #
+    /// # fn main() {}
+    /// ```
+    ///
+    /// [`SharderOptions::retain_shards`]:
+    /// struct.SharderOptions.html#structfield.retain_shards
+    /// [`reshard`]: #method.reshard
+    pub fn guild_counts(&self) -> HashMap<u64, usize> {
+        let queues = self.queues().collect::<Vec<_>>();
+
+        self.retained
+            .iter()
+            .flat_map(|retained| retained.keys())
+            .filter_map(|&shard_id| {
+                // A restarted shard is booted by a later boot loop, so its
+                // latest count is used.
+                queues
+                    .iter()
+                    .rev()
+                    .find_map(|queue| queue.guilds(shard_id))
+                    .map(|guilds| (shard_id, guilds))
+            })
+            .collect()
+    }
+
+    /// Returns the IDs of the shards that have not yet started booting, in the
+    /// order that they will be booted.
+    ///
+    /// This does not include the shard currently being booted.
+    pub fn pending_shard_ids(&self) -> Vec<u64> {
+        self.queues().flat_map(ShardQueue::pending).collect()
+    }
+
+    /// Drains the spawner until all of its shards have been spawned, resolving
+    /// to all of them sorted by ID.
+    ///
+    /// This is useful for bots which boot every shard before serving, and so
+    /// don't need to process shards as they're spawned. The number of shards
+    /// waited for is the [`total`] of the spawner, and so the future resolves
+    /// as soon as the last shard is spawned, without waiting for the boot
+    /// loop's delay after it.
+    ///
+    /// Shards retained via [`SharderOptions::retain_shards`] are included. If
+    /// the boot loop is stopped early, such as by a [`CancellationToken`], the
+    /// future resolves to the shards spawned until then.
+    ///
+    /// # Examples
+    ///
+    /// Boot all shards, and then print how many were booted:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity_sharder;
+    /// # extern crate tokio;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use futures::Future;
+    /// use serenity_sharder::{self, SharderOptions};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let spawner = serenity_sharder::spawn(SharderOptions::new(token))?;
+    ///
+    /// let future = spawner.ready().map(|shards| {
+    ///     println!("Booted {} shards", shards.len());
+    /// }).map_err(|why| eprintln!("Error booting shards: {}", why));
+    ///
+    /// tokio::run(future);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Resolves to the error of the boot loop if it failed.
+    ///
+    /// [`CancellationToken`]: struct.CancellationToken.html
+    /// [`SharderOptions::retain_shards`]:
+    /// struct.SharderOptions.html#structfield.retain_shards
+    /// [`total`]: #method.total
+    pub fn ready(mut self) -> impl Future<Item = Vec<Shard>, Error = Error> {
+        let mut shards = Vec::new();
+
+        future::poll_fn(move || {
+            loop {
+                let total = self.total();
+
+                if total == 0 || self.spawned < total {
+                    match self.poll()? {
+                        Async::Ready(Some(shard)) => {
+                            shards.push(shard);
+
+                            continue;
+                        },
+                        Async::Ready(None) => {},
+                        Async::NotReady => return Ok(Async::NotReady),
+                    }
+                }
+
+                break;
+            }
+
+            if let Some(retained) = self.retained.take() {
+                shards.extend(retained.into_iter().map(|(_, shard)| shard));
+            }
+
+            shards.sort_by_key(|shard| shard.shard_info()[0]);
+
+            Ok(Async::Ready(shards.drain(..).collect()))
+        })
+    }
+
+    /// Boots the shards of a new sharding strategy, yielding them from this
+    /// spawner's stream alongside any other shards.
+    ///
+    /// This is useful for re-sharding once a bot grows past its current shard
+    /// count, without dropping existing connections: the new shard set is
+    /// booted while the existing shards keep running. The same options are
+    /// used as for the original strategy, including the delay between boots
+    /// and the cancellation token, except that a [`BootOrder::Custom`] order
+    /// isn't used.
+    ///
+    /// The returned future is the boot loop of the new strategy, and must be
+    /// ran, such as by spawning it on the runtime. It resolves once the new
+    /// shards have been booted, and resolves to the error if booting them
+    /// failed. If this spawner's stream has already ended, it yields shards
+    /// again once the new shards are spawned.
+    ///
+    /// # Gateway sessions
+    ///
+    /// Existing shards are not affected: they keep their sessions and remain
+    /// owned by you. Until they're dropped, Discord delivers events for a
+    /// guild to both the existing shard and the new shard covering it, so
+    /// events may be received twice during a migration. Drop the existing
+    /// shards once the new set is ready, such as once the returned future
+    /// resolves.
+    ///
+    /// Every new shard starts a new session, counting towards the daily
+    /// session start limit, and sessions of the existing shards can't be
+    /// resumed under a new shard total.
+    ///
+    /// # Examples
+    ///
+    /// Re-shard to shards 0 to 19 of 20, spawning the new boot loop:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity_sharder;
+    /// # extern crate tokio;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use futures::Future;
+    /// use serenity_sharder::{self, SharderOptions, ShardingStrategy};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut spawner = serenity_sharder::spawn(SharderOptions::new(token))?;
+    ///
+    /// // Later, once the bot needs more shards:
+    /// let reshard = spawner.reshard(ShardingStrategy::Range(0, 19, 20));
+    /// tokio::spawn(reshard.map_err(|why| eprintln!("Error: {}", why)));
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Resolves to [`Error::InvalidStrategy`] when the values of the strategy
+    /// are invalid. Refer to [`ShardingStrategy::validate`] for more
+    /// information.
+    ///
+    /// # Logs
+    ///
+    /// [INFO] The strategy being re-sharded to.
+    ///
+    /// Refer to [`spawn`] for the logs of the boot loop.
+    ///
+    /// [`BootOrder::Custom`]: enum.BootOrder.html#variant.Custom
+    /// [`Error::InvalidStrategy`]: enum.Error.html#variant.InvalidStrategy
+    /// [`ShardingStrategy::validate`]:
+    /// enum.ShardingStrategy.html#method.validate
+    /// [`spawn`]: fn.spawn.html
+    pub fn reshard(
+        &mut self,
+        strategy: ShardingStrategy,
+    ) -> impl Future<Item = (), Error = Error> + Send {
+        if let Err(why) = strategy.validate() {
+            return Either::A(future::err(why));
+        }
+
+        info!("Re-sharding with strategy {:?}", strategy);
+
+        let options = self.reshard_options(strategy);
+        let (tx, rx) = channel::channel(options.channel_capacity);
+        // Readiness is only reported for the original strategy.
+        let (ready_tx, _) = oneshot::channel();
+        let queue = ShardQueue::default();
+
+        self.reshards.push(Reshard {
+            inner: rx,
+            queue: queue.clone(),
+        });
+
+        Either::B(spawn::boot_until_cancelled(options, queue, tx, ready_tx))
+    }
+
+    /// Restarts the retained shard with the given ID, without disturbing any
+    /// other shards.
+    ///
+    /// The shard is removed and closed like by [`shutdown`], sending a close
+    /// frame with the code 1000 and waiting for its connection to close. It's
+    /// then booted again by a new boot loop for only that ID, as if by
+    /// [`reshard`]. The shard is booted again even if it couldn't be closed
+    /// cleanly. Once booted, the new shard is retained in place of the old one
+    /// as this spawner's stream is polled. This requires
+    /// [`SharderOptions::retain_shards`] to be enabled.
+    ///
+    /// The returned future closes the shard and then runs its boot loop, and
+    /// must be ran.
+    ///
+    /// # Examples
+    ///
+    /// Restart shard 7, spawning its boot loop:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity_sharder;
+    /// # extern crate tokio;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use futures::Future;
+    /// use serenity_sharder::{self, SharderOptions};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let options = SharderOptions::new(token).with_retain_shards(true);
+    /// let mut spawner = serenity_sharder::spawn(options)?;
+    ///
+    /// // Later, once shard 7 is misbehaving:
+    /// let restart = spawner.restart(7);
+    /// tokio::spawn(restart.map_err(|why| eprintln!("Error: {}", why)));
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Resolves to [`Error::UnknownShard`] if no shard with the ID is retained,
+    /// such as when [`SharderOptions::retain_shards`] isn't enabled.
+    ///
+    /// Resolves to the error of the boot loop if booting the shard failed.
+    ///
+    /// # Logs
+    ///
+    /// [INFO] The ID of the shard being restarted.
+    ///
+    /// Refer to [`shutdown`] for the logs of closing the shard, and to
+    /// [`reshard`] for the other logs.
+    ///
+    /// [`Error::UnknownShard`]: enum.Error.html#variant.UnknownShard
+    /// [`SharderOptions::retain_shards`]:
+    /// struct.SharderOptions.html#structfield.retain_shards
+    /// [`reshard`]: #method.reshard
+    /// [`shutdown`]: #method.shutdown
+    pub fn restart(
+        &mut self,
+        shard_id: u64,
+    ) -> impl Future<Item = (), Error = Error> + Send {
+        let shard = self
+            .retained
+            .as_mut()
+            .and_then(|retained| retained.remove(&shard_id));
+        let shard = match shard {
+            Some(shard) => shard,
+            None => {
+                return Either::A(future::err(Error::UnknownShard {
+                    shard_id,
+                }));
+            },
+        };
+
+        info!("Restarting shard {}", shard_id);
+
+        let total = shard.shard_info()[1];
+        let closed = close_shards(&self.options, vec![shard]);
+
+        Either::B(self.restart_after(shard_id, total, closed))
+    }
+
+    /// Returns the number of shards that have been received from the boot loop
+    /// so far, whether yielded or retained.
+    ///
+    /// Together with [`total`], this can be used to report progress, such as
+    /// "shard 5 of 40 booted".
+    ///
+    /// [`total`]: #method.total
+    pub fn spawned(&self) -> u64 {
+        self.spawned
+    }
+
+    /// Returns the values of the resolved sharding strategy: the ID of the
+    /// first shard to start, the ID of the last, and the total number of
+    /// shards utilized by the bot across all instances.
+    ///
+    /// This is useful with [`ShardingStrategy::Autoshard`], where the total is
+    /// only known once Discord has been requested for it. Refer to
+    /// [`ShardingStrategy::values`] for more information.
+    ///
+    /// Returns `None` until the boot loop has resolved the strategy, or if the
+    /// strategy is an empty list. Strategies booted by [`reshard`] are not
+    /// included.
+    ///
+    /// [`ShardingStrategy::Autoshard`]:
+    /// enum.ShardingStrategy.html#variant.Autoshard
+    /// [`ShardingStrategy::values`]: enum.ShardingStrategy.html#method.values
+    /// [`reshard`]: #method.reshard
+    pub fn strategy_values(&self) -> Option<(u64, u64, u64)> {
+        self.queue.strategy()?.values()
+    }
+
+    /// Returns the number of shards that will eventually be spawned.
+    ///
+    /// This is the number of shards covered by the [`ShardingStrategy`], not
+    /// the total number of shards used by the bot across all instances. It's
+    /// reduced by shards cancelled via [`cancel_pending`].
+    ///
+    /// This is 0 until the boot loop has resolved the strategy, which for
+    /// [`ShardingStrategy::Autoshard`] requires a request to Discord.
+    ///
+    /// # Examples
+    ///
+    /// Print the progress of booting as shards are spawned:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity_sharder;
+    /// # extern crate tokio;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use futures::{future, Async, Stream};
+    /// use serenity_sharder::{self, SharderOptions};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut spawner = serenity_sharder::spawn(SharderOptions::new(token))?;
+    ///
+    /// let future = future::poll_fn(move || loop {
+    ///     match spawner.poll().map_err(|_| ())? {
+    ///         Async::Ready(Some(_shard)) => {
+    ///             println!(
+    ///                 "Shard {} of {} booted",
+    ///                 spawner.spawned(),
+    ///                 spawner.total()
+    ///             );
+    ///         },
+    ///         Async::Ready(None) => return Ok(Async::Ready(())),
+    ///         Async::NotReady => return Ok(Async::NotReady),
+    ///     }
+    /// });
+    ///
+    /// tokio::run(future);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ShardingStrategy`]: enum.ShardingStrategy.html
+    /// [`ShardingStrategy::Autoshard`]:
+    /// enum.ShardingStrategy.html#variant.Autoshard
+    /// [`cancel_pending`]: #method.cancel_pending
+    pub fn total(&self) -> u64 {
+        self.queues().map(ShardQueue::planned).sum()
+    }
+
+    /// Shuts down the sharder, stopping the boot loop and closing every shard
+    /// still held by the spawner.
+    ///
+    /// This cancels the [`cancellation_token`], which stops the boot loop
+    /// promptly and drops the shard currently being booted, if any. Note that
+    /// if the token is shared with other sharders, they're stopped too.
+    ///
+    /// Shards which have been spawned but not yet yielded, as well as shards
+    /// retained via [`SharderOptions::retain_shards`], are closed by sending a
+    /// close frame with the normal closure code of 1000, and then waiting for
+    /// the gateway to close their WebSocket connections. Shards which have
+    /// already been yielded are owned by you, and so are not closed by this.
+    ///
+    /// The returned future resolves once the connections of all shards held
+    /// by the spawner have closed. This is useful for rolling deploys, where
+    /// sessions should be torn down cleanly rather than when the process is
+    /// killed.
+    ///
+    /// # Examples
+    ///
+    /// Shut down the sharder once all shards have been retained:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity_sharder;
+    /// # extern crate tokio;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use futures::{Future, Stream};
+    /// use serenity_sharder::{self, SharderOptions};
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let mut options = SharderOptions::new(token);
+    /// options.retain_shards(true);
+    ///
+    /// let spawner = serenity_sharder::spawn(options)?;
+    ///
+    /// // Work with the shards here, and later in a shutdown handler:
+    /// let future = spawner.shutdown().map_err(|why| {
+    ///     eprintln!("Error shutting down: {:?}", why);
+    /// });
+    ///
+    /// tokio::run(future);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Resolves to the first error closing a shard, such as if its close frame
+    /// couldn't be sent, once every other shard has been closed.
+    ///
+    /// # Logs
+    ///
+    /// [INFO] When the sharder is being shut down.
+    /// [WARN] When a shard couldn't be closed cleanly.
+    /// [DEBUG] How many shards were closed.
+    ///
+    /// [`SharderOptions::retain_shards`]:
+    /// struct.SharderOptions.html#structfield.retain_shards
+    /// [`cancellation_token`]: #method.cancellation_token
+    pub fn shutdown(mut self) -> impl Future<Item = (), Error = Error> {
+        info!("Shutting down sharder");
+
+        self.options.cancellation_token.cancel();
+        self.cancel_pending();
+
+        self.close_held()
+    }
+
+    /// Returns a stream that yields shards until the given timeout elapses,
+    /// and then ends.
+    ///
+    /// This is useful for smoke tests and diagnosing partial boots, where a
+    /// boot taking longer than expected isn't an error: once the timeout
+    /// elapses the boot loop stops, and whichever shards were spawned in time
+    /// will have been yielded.
+    ///
+    /// # Examples
+    ///
+    /// Boot for up to 60 seconds and then print how many shards were spawned:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity_sharder;
+    /// # extern crate tokio;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use futures::{Future, Stream};
+    /// use serenity_sharder::{self, SharderOptions};
+    /// use std::{env, time::Duration};
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let options = SharderOptions::new(token);
+    ///
+    /// let future = serenity_sharder::spawn(options)?
+    ///     .take_until_timeout(Duration::from_secs(60))
+    ///     .collect()
+    ///     .map(|shards| {
+    ///         println!("Spawned {} shards", shards.len());
+    ///     })
+    ///     .map_err(|why| eprintln!("Error booting shards: {}", why));
+    ///
+    /// tokio::run(future);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn take_until_timeout(self, timeout: Duration) -> TakeUntilTimeout {
+        TakeUntilTimeout::new(self, timeout)
+    }
+
+    /// Closes the inner receivers, causing the boot loops to stop booting
+    /// further shards.
+    pub(crate) fn close(&mut self) {
+        self.inner.close();
+
+        for reshard in &mut self.reshards {
+            reshard.inner.close();
+        }
+    }
+
+    /// Closes the inner receivers, and then the connections of the retained
+    /// shards and of the shards that were spawned but not yet received.
+    fn close_held(&mut self) -> impl Future<Item = (), Error = Error> + Send {
+        self.close();
+
+        let mut shards = self.retained.as_mut().map_or_else(Vec::new, |map| {
+            map.drain().map(|(_, shard)| shard).collect()
+        });
+        let receivers = iter::once(&mut self.inner).chain(self.reshards
+            .iter_mut()
+            .map(|reshard| &mut reshard.inner));
+
+        // The receivers are closed, so the shards already sent to them are
+        // drained without waiting. The error of a failed boot loop has no
+        // shard to close.
+        for receiver in receivers {
+            loop {
+                match receiver.poll() {
+                    Ok(Async::Ready(Some(Ok(shard)))) => shards.push(shard),
+                    Ok(Async::Ready(Some(Err(_)))) => {},
+                    _ => break,
+                }
+            }
+        }
+
+        close_shards(&self.options, shards)
+    }
+
+    /// Returns the queues of all boot loops.
+    fn queues(&self) -> impl Iterator<Item = &ShardQueue> {
+        iter::once(&self.queue)
+            .chain(self.reshards.iter().map(|reshard| &reshard.queue))
+    }
+
+    /// Boots the shard with the given ID again once the given future, which
+    /// closes the old shard, has resolved.
+    ///
+    /// The boot loop is registered with the spawner immediately, but isn't
+    /// started until the old shard has closed.
+    fn restart_after(
+        &mut self,
+        shard_id: u64,
+        total: u64,
+        closed: impl Future<Item = (), Error = Error> + Send,
+    ) -> impl Future<Item = (), Error = Error> + Send {
+        let boot = self.reshard(ShardingStrategy::List(vec![shard_id], total));
+
+        closed.then(move |_| boot)
+    }
+
+    /// Returns the options to boot the shards of a new sharding strategy with.
+    ///
+    /// A custom boot order lists the shards of the original strategy, so it
+    /// isn't used for the new strategy, whose shards are booted in its own
+    /// order instead.
+    fn reshard_options(&self, strategy: ShardingStrategy) -> SharderOptions {
+        let mut options = self.options.clone();
+        options.strategy(strategy);
+
+        if let Some(BootOrder::Custom(_)) = options.boot_order {
+            options.boot_order = None;
+        }
+
+        options
+    }
+
+    /// Polls the inner receivers for the next shard, counting it as spawned.
+    ///
+    /// This ends once all of the receivers have ended.
+    fn poll_inner(&mut self) -> Poll<Option<Shard>, Error> {
+        let receivers = iter::once(&mut self.inner).chain(self.reshards
+            .iter_mut()
+            .map(|reshard| &mut reshard.inner));
+        let mut ended = true;
+
+        for receiver in receivers {
+            match receiver.poll() {
+                Ok(Async::Ready(Some(Ok(shard)))) => {
+                    self.spawned += 1;
+
+                    return Ok(Async::Ready(Some(shard)));
+                },
+                Ok(Async::Ready(Some(Err(why)))) => return Err(why),
+                Ok(Async::Ready(None)) | Err(()) => {},
+                Ok(Async::NotReady) => ended = false,
+            }
+        }
+
+        if ended {
+            Ok(Async::Ready(None))
+        } else {
+            Ok(Async::NotReady)
         }
     }
 }
 
+/// Closes the connection of a shard with the normal closure code of 1000,
+/// resolving once the gateway has closed the socket.
+fn close_shard(
+    mut shard: Shard,
+) -> impl Future<Item = (), Error = Error> + Send {
+    if let Err(why) = shard.shutdown_clean() {
+        return Either::A(future::err(Error::from(why)));
+    }
+
+    // The gateway echoes the close frame and then closes the socket, which
+    // ends the messages of the shard.
+    let closed = shard.messages().for_each(|_| Ok(())).map(move |_| {
+        drop(shard);
+    });
+
+    Either::B(closed.from_err())
+}
+
+/// Closes the connections of the given shards at the same time, resolving
+/// once all of them have closed.
+///
+/// Each shard is reported as stopped once closed, whether or not it closed
+/// cleanly. This resolves to the first error closing a shard, if any.
+pub(crate) fn close_shards(
+    options: &SharderOptions,
+    shards: Vec<Shard>,
+) -> impl Future<Item = (), Error = Error> + Send {
+    let closes = shards.into_iter().map(|shard| {
+        let options = options.clone();
+        let shard_id = shard.shard_info()[0];
+
+        close_shard(shard).then(move |res| {
+            if let Err(ref why) = res {
+                warn!("Error closing shard {}: {:?}", shard_id, why);
+            }
+
+            options.stopped(shard_id);
+
+            Ok::<_, ()>(res)
+        })
+    }).collect::<Vec<_>>();
+
+    future::join_all(closes).then(|res| {
+        let results = res.unwrap_or_default();
+        debug!("Closed {} shards", results.len());
+
+        results.into_iter().collect::<Result<Vec<_>, Error>>().map(|_| ())
+    })
+}
+
 impl Debug for ShardSpawner {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         f.debug_struct("ShardSpawner")
-            .field("inner", &"Unbounded Receiver of T Shard")
+            .field("closing", &self.closing.is_some())
+            .field("inner", &"Receiver of T Shard")
+            .field("options", &self.options)
+            .field("queue", &self.queue)
+            .field("ready", &self.ready)
+            .field("reshards", &self.reshards.iter().map(|reshard| {
+                &reshard.queue
+            }).collect::<Vec<_>>())
+            .field("retained", &self.retained.as_ref().map(|retained| {
+                retained.keys().collect::<Vec<_>>()
+            }))
+            .field("spawned", &self.spawned)
             .finish()
     }
 }
 
 impl Stream for ShardSpawner {
     type Item = Shard;
-    type Error = ();
+    type Error = Error;
 
     /// Polls the inner receiver.
     ///
+    /// When retaining shards, received shards are stored rather than yielded.
+    ///
+    /// If the boot loop failed, its error is yielded and the stream then ends.
+    /// If it failed because booting all of the shards timed out, the retained
+    /// shards and those spawned but not yet received are first closed like by
+    /// [`shutdown`], and the error is yielded once they've closed.
+    ///
     /// # Logs
     ///
     /// [TRACE] That the inner receiver is being polled.
+    /// [DEBUG] When a shard is retained.
+    ///
+    /// Refer to [`shutdown`] for the logs of closing the shards.
+    ///
+    /// [`shutdown`]: #method.shutdown
     fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
         trace!("Polling inner receiver");
 
-        self.inner.poll()
+        loop {
+            if let Some((mut closing, why)) = self.closing.take() {
+                // Errors closing a shard have already been logged.
+                return match closing.poll() {
+                    Ok(Async::NotReady) => {
+                        self.closing = Some((closing, why));
+
+                        Ok(Async::NotReady)
+                    },
+                    Ok(Async::Ready(())) | Err(_) => Err(why),
+                };
+            }
+
+            let shard = match self.poll_inner() {
+                Ok(Async::Ready(Some(shard))) => shard,
+                Ok(other) => return Ok(other),
+                Err(why @ Error::TotalBootTimeout { .. }) => {
+                    let closing: Closing = Box::new(self.close_held());
+                    self.closing = Some((closing, why));
+
+                    continue;
+                },
+                Err(why) => return Err(why),
+            };
+
+            let retained = match self.retained {
+                Some(ref mut retained) => retained,
+                None => return Ok(Async::Ready(Some(shard))),
+            };
+
+            let shard_id = shard.shard_info()[0];
+            debug!("Retaining shard {}", shard_id);
+
+            retained.insert(shard_id, shard);
+        }
+    }
+}
+
+#[cfg(test)]
+impl ShardSpawner {
+    /// Creates a spawner without a boot loop, returning the sender to send it
+    /// shards and errors in place of one, and the sender to mark it as ready.
+    ///
+    /// The spawner isn't ready until the ready sender is sent to, and fails to
+    /// become ready if it's dropped instead.
+    pub(crate) fn fixture(
+        options: SharderOptions,
+    ) -> (Self, mpsc::UnboundedSender<channel::Boot>, oneshot::Sender<()>) {
+        let (tx, rx) = mpsc::unbounded();
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let ready = AllReady::new(ready_rx.shared());
+        let spawner = Self::new(rx, ShardQueue::default(), ready, options);
+
+        (spawner, tx, ready_tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{
+        executor::{self, Notify, NotifyHandle},
+        sync::oneshot,
+        Async,
+        Future,
+        Stream,
+    };
+    use std::sync::{Arc, Mutex};
+    use {
+        plan,
+        BootOrder,
+        Error,
+        ShardSpawner,
+        SharderOptions,
+        ShardingStrategy,
+    };
+
+    struct Noop;
+
+    impl Notify for Noop {
+        fn notify(&self, _: usize) {}
+    }
+
+    #[test]
+    fn test_boot_error_ends_stream() {
+        let options = SharderOptions::new("a");
+        let (mut spawner, tx, _ready) = ShardSpawner::fixture(options);
+
+        tx.unbounded_send(Err(Error::ShardPanicked {
+            shard_id: 2,
+        })).unwrap();
+        drop(tx);
+
+        match spawner.poll() {
+            Err(Error::ShardPanicked { shard_id: 2 }) => {},
+            _ => panic!("Expected the boot loop's error"),
+        }
+
+        match spawner.poll() {
+            Ok(Async::Ready(None)) => {},
+            _ => panic!("Expected the stream to end"),
+        }
+
+        assert_eq!(spawner.spawned(), 0);
+    }
+
+    #[test]
+    fn test_cancel_pending() {
+        let options = SharderOptions::new("a");
+        let token = options.cancellation_token.clone();
+        let (spawner, tx, _ready) = ShardSpawner::fixture(options.clone());
+        spawner.queue.extend(vec![0, 1, 2]);
+        spawner.queue.pop_round(1);
+
+        let (other, _other_tx, _other_ready) = ShardSpawner::fixture(options);
+        other.queue.extend(vec![3, 4]);
+        let spawner = spawner.merge(other);
+
+        spawner.cancel_pending();
+        assert!(spawner.pending_shard_ids().is_empty());
+        // The shard already popped is still being booted.
+        assert_eq!(spawner.total(), 1);
+
+        // Unlike shutting down, the boot loops keep running.
+        assert!(!token.is_cancelled());
+        assert!(!tx.is_closed());
+    }
+
+    #[test]
+    fn test_merge() {
+        let options = SharderOptions::new("a");
+        let (spawner, tx, _ready) = ShardSpawner::fixture(options.clone());
+        spawner.queue.extend(vec![0, 1]);
+        drop(tx);
+
+        let (other, tx, _other_ready) = ShardSpawner::fixture(options);
+        other.queue.extend(vec![2, 3, 4]);
+
+        let spawner = spawner.merge(other);
+        assert_eq!(spawner.total(), 5);
+        assert_eq!(spawner.pending_shard_ids(), vec![0, 1, 2, 3, 4]);
+
+        // The merged spawner doesn't end until both boot loops have ended.
+        let notify = NotifyHandle::from(Arc::new(Noop));
+        let mut spawner = executor::spawn(spawner);
+        let poll = spawner.poll_stream_notify(&notify, 0);
+        assert!(poll.unwrap().is_not_ready());
+        drop(tx);
+        let poll = spawner.poll_stream_notify(&notify, 0);
+        assert!(poll.unwrap().is_ready());
+    }
+
+    #[test]
+    fn test_partition_key() {
+        assert_eq!(ShardSpawner::partition_key(0, 4), 0);
+        assert_eq!(ShardSpawner::partition_key(3, 4), 3);
+        assert_eq!(ShardSpawner::partition_key(4, 4), 0);
+        assert_eq!(ShardSpawner::partition_key(17, 8), 1);
+        assert_eq!(ShardSpawner::partition_key(39, 16), 7);
+        assert_eq!(ShardSpawner::partition_key(5, 1), 0);
+        assert_eq!(ShardSpawner::partition_key(5, 0), 0);
+    }
+
+    #[test]
+    fn test_ready() {
+        let options = SharderOptions::new("a");
+        let (spawner, tx, _ready) = ShardSpawner::fixture(options.clone());
+        drop(tx);
+
+        assert!(spawner.ready().wait().unwrap().is_empty());
+
+        let (spawner, tx, _ready) = ShardSpawner::fixture(options);
+
+        tx.unbounded_send(Err(Error::ShardPanicked {
+            shard_id: 1,
+        })).unwrap();
+
+        match spawner.ready().wait() {
+            Err(Error::ShardPanicked { shard_id: 1 }) => {},
+            _ => panic!("Expected the boot loop's error"),
+        }
+    }
+
+    #[test]
+    fn test_reshard() {
+        let options = SharderOptions::new("a");
+        let (mut spawner, tx, _ready) = ShardSpawner::fixture(options);
+        drop(tx);
+
+        match spawner.reshard(ShardingStrategy::Range(4, 2, 8)).wait() {
+            Err(Error::InvalidStrategy { .. }) => {},
+            _ => panic!("Expected the strategy to be invalid"),
+        }
+        assert!(spawner.reshards.is_empty());
+
+        // The new boot loop is registered at once, but boots nothing until
+        // it's ran.
+        let reshard = spawner.reshard(ShardingStrategy::Range(8, 9, 10));
+        assert_eq!(spawner.reshards.len(), 1);
+        assert_eq!(spawner.total(), 0);
+
+        // The spawner doesn't end until the new boot loop has ended.
+        let notify = NotifyHandle::from(Arc::new(Noop));
+        let mut spawner = executor::spawn(spawner);
+        let poll = spawner.poll_stream_notify(&notify, 0);
+        assert!(poll.unwrap().is_not_ready());
+        drop(reshard);
+        let poll = spawner.poll_stream_notify(&notify, 0);
+        assert!(poll.unwrap().is_ready());
+    }
+
+    #[test]
+    fn test_reshard_custom_order() {
+        let mut spawner = ShardSpawner::from_shards(Vec::new());
+        spawner.options.boot_order(BootOrder::Custom(vec![1, 2]));
+
+        // The custom order is of the original strategy, and so isn't checked
+        // against the shards of the new one.
+        let strategy = ShardingStrategy::List(vec![7], 8);
+        let options = spawner.reshard_options(strategy);
+        let plan = plan(&options).wait().unwrap();
+        assert_eq!(plan.shard_ids, vec![7]);
+        assert_eq!(options.boot_order, None);
+
+        spawner.options.boot_order(BootOrder::Descending);
+        let options = spawner.reshard_options(ShardingStrategy::Range(0, 2, 3));
+        assert_eq!(plan(&options).wait().unwrap().shard_ids, vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_restart_after_close() {
+        let options = SharderOptions::new("a").with_retain_shards(true);
+        let (mut spawner, _tx, _ready) = ShardSpawner::fixture(options);
+        let notify = NotifyHandle::from(Arc::new(Noop));
+
+        // The boot loop is registered at once, but isn't started until the
+        // old shard has closed.
+        let (_closed_tx, closed_rx) = oneshot::channel();
+        let restart = spawner.restart_after(3, 8, closed_rx.from_err());
+        let mut restart = executor::spawn(restart);
+        let poll = restart.poll_future_notify(&notify, 0);
+        assert!(poll.unwrap().is_not_ready());
+        assert_eq!(spawner.reshards.len(), 1);
+        assert_eq!(spawner.total(), 0);
+
+        // An invalid strategy fails the boot loop without booting, which is
+        // only observed once the old shard has closed.
+        let (closed_tx, closed_rx) = oneshot::channel();
+        let restart = spawner.restart_after(3, 2, closed_rx.from_err());
+        let mut restart = executor::spawn(restart);
+        let poll = restart.poll_future_notify(&notify, 0);
+        assert!(poll.unwrap().is_not_ready());
+
+        closed_tx.send(()).unwrap();
+
+        match restart.poll_future_notify(&notify, 0) {
+            Err(Error::InvalidStrategy { .. }) => {},
+            _ => panic!("Expected the boot loop's error"),
+        }
+    }
+
+    #[test]
+    fn test_restart_unknown_shard() {
+        let options = SharderOptions::new("a");
+        let (mut spawner, _tx, _ready) = ShardSpawner::fixture(options);
+        let unknown = Err(Error::UnknownShard {
+            shard_id: 3,
+        });
+
+        // Shards aren't retained.
+        assert_eq!(spawner.restart(3).wait(), unknown);
+
+        let options = SharderOptions::new("a").with_retain_shards(true);
+        let (mut spawner, _tx, _ready) = ShardSpawner::fixture(options);
+
+        assert_eq!(spawner.restart(3).wait(), unknown);
+        assert!(spawner.reshards.is_empty());
+    }
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send + 'static>() {}
+        fn assert_sync<T: Sync>() {}
+
+        assert_send::<ShardSpawner>();
+        // The spawner isn't Sync, but can be shared behind a lock.
+        assert_sync::<Mutex<ShardSpawner>>();
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let options = SharderOptions::new("a");
+        let token = options.cancellation_token.clone();
+        let (spawner, tx, _ready) = ShardSpawner::fixture(options);
+        let queue = spawner.queue.clone();
+        queue.extend(vec![0, 1, 2]);
+
+        tx.unbounded_send(Err(Error::ShardPanicked {
+            shard_id: 0,
+        })).unwrap();
+
+        // The boot loop's sender is still alive, but the closed receiver is
+        // only drained rather than waited on.
+        assert!(spawner.shutdown().wait().is_ok());
+        assert!(token.is_cancelled());
+        assert!(tx.is_closed());
+        assert!(queue.pending().is_empty());
+        assert_eq!(queue.planned(), 0);
+    }
+
+    #[test]
+    fn test_total_boot_timeout_closes_shards() {
+        let options = SharderOptions::new("a").with_retain_shards(true);
+        let (mut spawner, tx, _ready) = ShardSpawner::fixture(options);
+        let timeout = Error::TotalBootTimeout {
+            booted: 1,
+            total: 2,
+        };
+        tx.unbounded_send(Err(timeout)).unwrap();
+
+        match spawner.poll() {
+            Err(Error::TotalBootTimeout { booted: 1, total: 2 }) => {},
+            _ => panic!("Expected the boot loop's error"),
+        }
+
+        // The receiver was closed to drain the shards to close, and so ends
+        // despite the boot loop's sender being alive.
+        assert!(tx.is_closed());
+        assert!(spawner.poll().unwrap().is_ready());
     }
 }
@@ -1,16 +1,46 @@
+use futures::{
+    future::{self, Either},
+    Future,
+};
+use gateway::{self, BotGateway};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use serenity::http::Client as HttpClient;
+use std::{collections::HashSet, sync::Arc};
+use Error;
+
 /// The strategy to use for starting shards in a sharder context.
 ///
 /// Refer to each variant for more information.
 ///
 /// Defaults to [`ShardingStrategy::Autoshard`].
 ///
+/// # Serde
+///
+/// With the `serde` feature enabled, strategies can be serialized and
+/// deserialized, such as from a config file. Variants are represented by
+/// their name in snake case, with any values as an array:
+///
+/// ```rust,ignore
+/// "autoshard"
+/// {"range": [0, 19, 40]}
+/// {"list": [[0, 3, 7, 12], 16]}
+/// ```
+///
 /// [`ShardingStrategy::Autoshard`]: #variant.Autoshard
 #[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ShardingStrategy {
     /// Specify that a default number of shards will be used. This is
     /// proportionate to the size of your bot in guilds.
     ///
+    /// The number of shards is the number recommended by Discord, retrieved
+    /// when the strategy is [resolved].
+    ///
     /// Typically you should use this.
+    ///
+    /// [resolved]: #method.resolve
     Autoshard,
     /// Specify that a range of shards will be started.
     ///
@@ -31,7 +61,27 @@ pub enum ShardingStrategy {
     ///
     /// [`ShardingOptions`]: struct.ShardingOptions.html
     Range(u64, u64, u64),
+    /// Specify that a list of shards, which need not be contiguous, will be
+    /// started. The second value is the total number of shards.
+    ///
+    /// This is useful when spreading shards across machines of differing
+    /// capacity.
+    ///
+    /// Shards are started in the order of the list. Each ID may only appear
+    /// once.
+    ///
+    /// # Examples
+    ///
+    /// Start shards 0, 3, 7, and 12 of 16 total:
+    ///
+    /// ```rust,no_run
+    /// use serenity_sharder::ShardingStrategy;
+    ///
+    /// let strategy = ShardingStrategy::list(vec![0, 3, 7, 12], 16);
+    /// ```
+    List(Vec<u64>, u64),
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     Nonexhaustive,
 }
 
@@ -52,8 +102,255 @@ impl ShardingStrategy {
     /// just a wrapper over it.
     ///
     /// [`ShardingStrategy::Range`]: #variant.Range
-    pub fn range(start: u64, amount: u64, total: u64) -> Self {
-        ShardingStrategy::Range(start, amount, total)
+    pub fn range(start: u64, end: u64, total: u64) -> Self {
+        ShardingStrategy::Range(start, end, total)
+    }
+
+    /// Returns a list strategy.
+    ///
+    /// Refer to [`ShardingStrategy::List`] for more information, as this is
+    /// just a wrapper over it.
+    ///
+    /// [`ShardingStrategy::List`]: #variant.List
+    pub fn list(shard_ids: Vec<u64>, total: u64) -> Self {
+        ShardingStrategy::List(shard_ids, total)
+    }
+
+    /// Returns the identify concurrency bucket of a shard.
+    ///
+    /// Shards in different buckets may identify at the same time, while shards
+    /// in the same bucket must identify one after another. The bucket of a
+    /// shard is `shard_id % max_concurrency`.
+    ///
+    /// A `max_concurrency` of 0 is treated as 1.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serenity_sharder::ShardingStrategy;
+    ///
+    /// assert_eq!(ShardingStrategy::bucket_for(17, 16), 1);
+    /// ```
+    pub fn bucket_for(shard_id: u64, max_concurrency: u64) -> u64 {
+        shard_id % max_concurrency.max(1)
+    }
+
+    /// Returns the IDs of the shards to boot, grouped by their identify
+    /// concurrency bucket.
+    ///
+    /// The index of each group is the bucket it represents, as returned by
+    /// [`bucket_for`]. Groups are empty if no shards to boot fall into them.
+    ///
+    /// Returns `None` if the strategy has no defined shards, such as with
+    /// [`ShardingStrategy::Autoshard`].
+    ///
+    /// # Examples
+    ///
+    /// Group shards 0 through 5 into 4 buckets:
+    ///
+    /// ```rust
+    /// use serenity_sharder::ShardingStrategy;
+    ///
+    /// let strategy = ShardingStrategy::Range(0, 5, 10);
+    /// let buckets = strategy.buckets(4).unwrap();
+    ///
+    /// assert_eq!(buckets, vec![vec![0, 4], vec![1, 5], vec![2], vec![3]]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when this is called on an undocumented variant.
+    ///
+    /// [`ShardingStrategy::Autoshard`]: #variant.Autoshard
+    /// [`bucket_for`]: #method.bucket_for
+    pub fn buckets(&self, max_concurrency: u64) -> Option<Vec<Vec<u64>>> {
+        let (shard_ids, _) = self.shard_ids()?;
+        let max_concurrency = max_concurrency.max(1);
+        let mut buckets = vec![Vec::new(); max_concurrency as usize];
+
+        for id in shard_ids {
+            buckets[Self::bucket_for(id, max_concurrency) as usize].push(id);
+        }
+
+        Some(buckets)
+    }
+
+    /// Resolves the strategy into one with a defined set of shards to boot.
+    ///
+    /// For [`ShardingStrategy::Autoshard`], this requests the number of shards
+    /// recommended by Discord for the bot with the given token, and resolves
+    /// to a [`ShardingStrategy::Range`] booting all of them. Otherwise, this
+    /// resolves to a clone of the strategy without making any requests.
+    ///
+    /// # Examples
+    ///
+    /// Print the number of shards Discord recommends:
+    ///
+    /// ```rust,no_run
+    /// # extern crate futures;
+    /// # extern crate serenity_sharder;
+    /// # extern crate tokio;
+    /// #
+    /// # use std::error::Error;
+    /// #
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// #
+    /// use futures::Future;
+    /// use serenity_sharder::ShardingStrategy;
+    /// use std::env;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let future = ShardingStrategy::Autoshard
+    ///     .resolve(&token)
+    ///     .map(|strategy| println!("Discord recommends {:?}", strategy))
+    ///     .map_err(|why| println!("Error resolving strategy: {:?}", why));
+    ///
+    /// tokio::run(future);
+    /// #
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Serenity`] if the HTTP client could not be built, or
+    /// if requesting the gateway information failed or was unsuccessful, such
+    /// as when the token is invalid.
+    ///
+    /// # Panics
+    ///
+    /// Panics when this is called on an undocumented variant.
+    ///
+    /// [`Error::Serenity`]: enum.Error.html#variant.Serenity
+    /// [`ShardingStrategy::Autoshard`]: #variant.Autoshard
+    /// [`ShardingStrategy::Range`]: #variant.Range
+    pub fn resolve(
+        &self,
+        token: &str,
+    ) -> impl Future<Item = ShardingStrategy, Error = Error> + Send {
+        self.resolve_gateway(token, None).map(|(strategy, _)| strategy)
+    }
+
+    /// Resolves the strategy into one with a defined set of shards to boot,
+    /// along with the gateway information if it was retrieved to do so.
+    ///
+    /// The given HTTP client is used to retrieve the gateway information, if
+    /// any.
+    pub(crate) fn resolve_gateway(
+        &self,
+        token: &str,
+        client: Option<Arc<HttpClient>>,
+    ) -> impl Future<
+        Item = (ShardingStrategy, Option<BotGateway>),
+        Error = Error,
+    > + Send {
+        match *self {
+            ShardingStrategy::Autoshard => {
+                Either::A(gateway::bot(token, client).map(|gateway| {
+                    // Discord should never recommend no shards, but a total
+                    // of 0 would be an invalid shard array.
+                    if gateway.shards == 0 {
+                        warn!("Discord recommended 0 shards, using 1");
+                    }
+
+                    let total = gateway.shards.max(1);
+                    let strategy = ShardingStrategy::Range(0, total - 1, total);
+
+                    (strategy, Some(gateway))
+                }))
+            },
+            ShardingStrategy::Nonexhaustive => {
+                unreachable!("Don't specify this")
+            },
+            ref other => Either::B(future::ok((other.clone(), None))),
+        }
+    }
+
+    /// Returns the IDs of the shards that should be booted, in order, and the
+    /// total number of shards utilized by the bot across all instances.
+    ///
+    /// Returns `None` for [`ShardingStrategy::Autoshard`], as it's dynamic.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serenity_sharder::ShardingStrategy;
+    ///
+    /// let strategy = ShardingStrategy::Range(2, 4, 10);
+    /// assert_eq!(strategy.shard_ids(), Some((vec![2, 3, 4], 10)));
+    ///
+    /// let strategy = ShardingStrategy::List(vec![7, 1], 10);
+    /// assert_eq!(strategy.shard_ids(), Some((vec![7, 1], 10)));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when this is called on an undocumented variant.
+    ///
+    /// [`ShardingStrategy::Autoshard`]: #variant.Autoshard
+    pub fn shard_ids(&self) -> Option<(Vec<u64>, u64)> {
+        use ShardingStrategy::*;
+
+        match *self {
+            Range(start, end, total) => Some(((start..=end).collect(), total)),
+            List(ref shard_ids, total) => Some((shard_ids.clone(), total)),
+            Autoshard => None,
+            Nonexhaustive => unreachable!("Don't specify this"),
+        }
+    }
+
+    /// Validates that the values of the strategy are sane.
+    ///
+    /// This is done by [`spawn`], so that an invalid strategy is an error at
+    /// startup rather than causing the boot loop to misbehave.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serenity_sharder::ShardingStrategy;
+    ///
+    /// assert!(ShardingStrategy::Range(0, 9, 10).validate().is_ok());
+    /// assert!(ShardingStrategy::Range(50, 40, 10).validate().is_err());
+    /// assert!(ShardingStrategy::List(vec![3, 3], 8).validate().is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidStrategy`] when the first shard's ID is greater
+    /// than the last's, the last shard's ID is not less than the total, or the
+    /// total is 0.
+    ///
+    /// Also returns [`Error::InvalidStrategy`] when a list contains the same ID
+    /// more than once, as the shard would be booted twice, using an extra
+    /// session start.
+    ///
+    /// [`Error::InvalidStrategy`]: enum.Error.html#variant.InvalidStrategy
+    /// [`spawn`]: fn.spawn.html
+    pub fn validate(&self) -> Result<(), Error> {
+        let (start, end, total) = match self.values() {
+            Some(values) => values,
+            None => return Ok(()),
+        };
+
+        let invalid = Error::InvalidStrategy {
+            end,
+            start,
+            total,
+        };
+
+        if start > end || end >= total || total == 0 {
+            return Err(invalid);
+        }
+
+        if let ShardingStrategy::List(ref shard_ids, _) = *self {
+            let mut seen = HashSet::with_capacity(shard_ids.len());
+
+            if !shard_ids.iter().all(|id| seen.insert(id)) {
+                return Err(invalid);
+            }
+        }
+
+        Ok(())
     }
 
     /// Returns the values of the shards that should be booted.
@@ -61,9 +358,13 @@ impl ShardingStrategy {
     /// The three values in the tuple are, in order:
     ///
     /// - the ID of the first shard to start
-    /// - the number of shards to start, incrementing the ID for each
+    /// - the ID of the last shard to start, incrementing the ID for each
     /// - the total number of shards utilized by the bot across all instances
     ///
+    /// For [`ShardingStrategy::List`], the first two values are the lowest and
+    /// highest IDs in the list, and not every ID between them is started:
+    /// use [`shard_ids`] for the exact IDs. An empty list has no values.
+    ///
     /// # Examples
     ///
     /// Create a sharding strategy booting shards 0 through 4, inclusively, out
@@ -72,8 +373,8 @@ impl ShardingStrategy {
     /// ```rust
     /// use serenity_sharder::ShardingStrategy;
     ///
-    /// let strategy = ShardingStrategy::Range(0, 5, 10);
-    /// assert_eq!(strategy.values(), Some((0, 5, 10)));
+    /// let strategy = ShardingStrategy::Range(0, 4, 10);
+    /// assert_eq!(strategy.values(), Some((0, 4, 10)));
     /// ```
     ///
     /// Assert that [`ShardingStrategy::Autoshard`] has no defined range, as
@@ -91,11 +392,19 @@ impl ShardingStrategy {
     /// Panics when this is called on an undocumented variant.
     ///
     /// [`ShardingStrategy::Autoshard`]: #variant.Autoshard
+    /// [`ShardingStrategy::List`]: #variant.List
+    /// [`shard_ids`]: #method.shard_ids
     pub fn values(&self) -> Option<(u64, u64, u64)> {
         use ShardingStrategy::*;
 
         match *self {
             Range(start, end, total) => Some((start, end, total)),
+            List(ref shard_ids, total) => {
+                let start = *shard_ids.iter().min()?;
+                let end = *shard_ids.iter().max()?;
+
+                Some((start, end, total))
+            },
             Autoshard => None,
             Nonexhaustive => unreachable!("Don't specify this"),
         }
@@ -111,17 +420,51 @@ impl Default for ShardingStrategy {
 #[cfg(test)]
 mod tests {
     use super::ShardingStrategy;
+    use Error;
 
     #[test]
     fn test_auto() {
         assert_eq!(ShardingStrategy::Autoshard, ShardingStrategy::auto());
     }
 
+    #[test]
+    fn test_buckets_partition() {
+        let strategy = ShardingStrategy::Range(3, 20, 40);
+        let buckets = strategy.buckets(4).unwrap();
+        assert_eq!(buckets.len(), 4);
+
+        let mut ids = buckets.iter().flatten().cloned().collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, (3..=20).collect::<Vec<_>>());
+
+        for (bucket, ids) in buckets.iter().enumerate() {
+            assert!(ids.iter().all(|id| {
+                ShardingStrategy::bucket_for(*id, 4) == bucket as u64
+            }));
+        }
+    }
+
+    #[test]
+    fn test_buckets_autoshard() {
+        assert!(ShardingStrategy::Autoshard.buckets(16).is_none());
+    }
+
     #[test]
     fn test_default() {
         assert_eq!(ShardingStrategy::default(), ShardingStrategy::Autoshard);
     }
 
+    #[test]
+    fn test_list() {
+        let strategy = ShardingStrategy::list(vec![12, 0, 7, 3], 16);
+
+        assert_eq!(strategy, ShardingStrategy::List(vec![12, 0, 7, 3], 16));
+        assert_eq!(strategy.values(), Some((0, 12, 16)));
+        assert_eq!(strategy.shard_ids(), Some((vec![12, 0, 7, 3], 16)));
+        assert_eq!(strategy.buckets(2), Some(vec![vec![12, 0], vec![7, 3]]));
+        assert!(ShardingStrategy::list(vec![], 16).values().is_none());
+    }
+
     #[test]
     fn test_range() {
         let (lhs, rhs) = (
@@ -137,4 +480,48 @@ mod tests {
     fn test_strategy_panic() {
         let _ = ShardingStrategy::Nonexhaustive.values();
     }
+
+    #[test]
+    fn test_validate() {
+        assert!(ShardingStrategy::Autoshard.validate().is_ok());
+        assert!(ShardingStrategy::Range(0, 0, 1).validate().is_ok());
+        assert!(ShardingStrategy::list(vec![9, 2], 10).validate().is_ok());
+
+        for strategy in vec![
+            ShardingStrategy::Range(50, 40, 10),
+            ShardingStrategy::Range(0, 10, 10),
+            ShardingStrategy::Range(0, 0, 0),
+            ShardingStrategy::list(vec![0], 0),
+            ShardingStrategy::list(vec![2, 10], 10),
+            ShardingStrategy::list(vec![3, 3], 8),
+            ShardingStrategy::list(vec![1, 4, 1], 8),
+        ] {
+            match strategy.validate() {
+                Err(Error::InvalidStrategy { .. }) => {},
+                other => panic!("Unexpected result: {:?}", other),
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde_json;
+
+        for &(ref strategy, json) in &[
+            (ShardingStrategy::Autoshard, r#""autoshard""#),
+            (ShardingStrategy::Range(0, 19, 40), r#"{"range":[0,19,40]}"#),
+            (ShardingStrategy::List(vec![0, 3], 16), r#"{"list":[[0,3],16]}"#),
+        ] {
+            assert_eq!(serde_json::to_string(strategy).unwrap(), json);
+            assert_eq!(
+                serde_json::from_str::<ShardingStrategy>(json).unwrap(),
+                *strategy
+            );
+        }
+
+        assert!(serde_json::from_str::<ShardingStrategy>(
+            r#""nonexhaustive""#
+        ).is_err());
+    }
 }
@@ -0,0 +1,84 @@
+use futures::{Async, Future, Poll, Stream};
+use serenity::gateway::Shard;
+use std::time::{Duration, Instant};
+use tokio::timer::Delay;
+use {Error, ShardSpawner};
+
+/// A stream of shards which ends once a timeout has elapsed, regardless of
+/// whether all shards have been spawned.
+///
+/// This is returned by [`ShardSpawner::take_until_timeout`]. When the timeout
+/// elapses the underlying receiver is closed, causing the boot loop to stop
+/// booting any further shards.
+///
+/// Unlike an error, the timeout elapsing is expected: the stream simply ends,
+/// having yielded whichever shards were spawned in time.
+///
+/// [`ShardSpawner::take_until_timeout`]:
+/// struct.ShardSpawner.html#method.take_until_timeout
+#[derive(Debug)]
+pub struct TakeUntilTimeout {
+    delay: Delay,
+    inner: ShardSpawner,
+}
+
+impl TakeUntilTimeout {
+    pub(crate) fn new(inner: ShardSpawner, timeout: Duration) -> Self {
+        Self {
+            delay: Delay::new(Instant::now() + timeout),
+            inner,
+        }
+    }
+}
+
+impl Stream for TakeUntilTimeout {
+    type Item = Shard;
+    type Error = Error;
+
+    /// Polls the timeout, and then the inner spawner if the timeout has not
+    /// yet elapsed.
+    ///
+    /// # Logs
+    ///
+    /// [DEBUG] When the timeout has elapsed and the stream is ending.
+    /// [WARN] When there was an error polling the timer, which ends the stream.
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        match self.delay.poll() {
+            Ok(Async::NotReady) => {},
+            Ok(Async::Ready(())) => {
+                debug!("Spawn timeout elapsed, ending stream");
+                self.inner.close();
+
+                return Ok(Async::Ready(None));
+            },
+            Err(why) => {
+                warn!("Error polling spawn timeout, ending stream: {:?}", why);
+                self.inner.close();
+
+                return Ok(Async::Ready(None));
+            },
+        }
+
+        self.inner.poll()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::Stream;
+    use std::time::Duration;
+    use tokio::runtime::current_thread;
+    use {ShardSpawner, SharderOptions};
+
+    #[test]
+    fn test_timeout_ends_stream() {
+        let options = SharderOptions::new("a");
+        let (spawner, tx, _ready) = ShardSpawner::fixture(options);
+        let stream = spawner.take_until_timeout(Duration::from_millis(10));
+
+        let shards = current_thread::block_on_all(stream.collect()).unwrap();
+
+        assert!(shards.is_empty());
+        assert!(tx.is_closed());
+    }
+}